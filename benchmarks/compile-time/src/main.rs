@@ -0,0 +1,146 @@
+//! Compares how long a crate with many retrieval traits takes to compile, with and without shared switches.
+//!
+//! Run with `cargo run --release -p compile-time-benchmark -- [traits] [implementations per trait] [runs]`.\
+//! Two otherwise identical crates are generated in `target/compile-time-benchmark`, sharing a target directory so that dependencies are only built once.\
+//! Each is then rebuilt after touching its source, and the fastest run is reported.
+#![warn(clippy::pedantic)]
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// How the generated crate gets its switches.
+#[derive(Clone, Copy)]
+enum Variant {
+    /// Every retrieval trait generates its own.
+    Separate,
+    /// Every retrieval trait takes them from one pool.
+    Shared,
+}
+
+impl Variant {
+    fn name(self) -> &'static str {
+        match self {
+            Variant::Separate => "separate",
+            Variant::Shared => "shared",
+        }
+    }
+}
+
+/// Generates a crate's source, with the given amount of retrieval traits, each sent the given amount of implementations.
+fn source(variant: Variant, traits: u32, implementations: u32) -> String {
+    let mut source = String::from("use retrieval::*;\n\n");
+    if let Variant::Shared = variant {
+        let _ = writeln!(
+            source,
+            "shared_switches!({});\n",
+            traits * (implementations + 1)
+        );
+    }
+
+    for index in 0..traits {
+        let _ = writeln!(
+            source,
+            "#[retrieve]\npub trait Registry{index} {{\n    const VALUE: u32 = 0;\n}}\n"
+        );
+        for value in 0..implementations {
+            let _ = writeln!(
+                source,
+                "#[send]\nimpl Registry{index} {{\n    const VALUE: u32 = {value};\n}}\n"
+            );
+        }
+        let _ = writeln!(
+            source,
+            "const _: () = assert!(Registry{index}::QUANTITY == {implementations});\n"
+        );
+    }
+
+    source
+}
+
+/// Writes the crate, and returns the path to its manifest.
+fn generate(
+    root: &Path,
+    directory: &Path,
+    variant: Variant,
+    traits: u32,
+    implementations: u32,
+) -> std::io::Result<PathBuf> {
+    let directory = directory.join(variant.name());
+    std::fs::create_dir_all(directory.join("src"))?;
+
+    let manifest = directory.join("Cargo.toml");
+    std::fs::write(
+        &manifest,
+        format!(
+            "[package]\nname = \"{}\"\nversion = \"0.0.0\"\nedition = \"2024\"\npublish = false\n\n[workspace]\n\n[dependencies]\nretrieval = {{path = {:?}}}\n",
+            variant.name(),
+            root.display().to_string(),
+        ),
+    )?;
+    // Reuse the resolved versions, so that nothing needs to be fetched.
+    let lock = root.join("Cargo.lock");
+    if lock.exists() {
+        std::fs::copy(lock, directory.join("Cargo.lock"))?;
+    }
+    std::fs::write(
+        directory.join("src/lib.rs"),
+        source(variant, traits, implementations),
+    )?;
+
+    Ok(manifest)
+}
+
+/// Builds the crate, and returns how long it took.
+fn build(manifest: &Path, target: &Path) -> Duration {
+    let start = Instant::now();
+    let status = Command::new(std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+        .args(["build", "--quiet", "--manifest-path"])
+        .arg(manifest)
+        .env("CARGO_TARGET_DIR", target)
+        .status()
+        .expect("Could not run cargo.");
+    assert!(status.success(), "{} failed to build.", manifest.display());
+    start.elapsed()
+}
+
+/// Marks the crate's source as changed, so that only it is rebuilt.
+fn touch(manifest: &Path) -> std::io::Result<()> {
+    let lib = manifest.with_file_name("src").join("lib.rs");
+    let source = std::fs::read_to_string(&lib)?;
+    std::fs::write(lib, source)
+}
+
+fn main() -> std::io::Result<()> {
+    let mut arguments = std::env::args()
+        .skip(1)
+        .map(|argument| argument.parse::<u32>().expect("Expected a number."));
+    let traits = arguments.next().unwrap_or(20);
+    let implementations = arguments.next().unwrap_or(5);
+    let runs = arguments.next().unwrap_or(3).max(1);
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../..")
+        .canonicalize()?;
+    let directory = root.join("target/compile-time-benchmark");
+    let target = directory.join("target");
+
+    println!("{traits} retrieval traits, each with {implementations} implementations.");
+    for variant in [Variant::Separate, Variant::Shared] {
+        let manifest = generate(&root, &directory, variant, traits, implementations)?;
+        // Builds the dependencies, which aren't being measured.
+        build(&manifest, &target);
+
+        let mut fastest = Duration::MAX;
+        for _ in 0..runs {
+            touch(&manifest)?;
+            fastest = fastest.min(build(&manifest, &target));
+        }
+        println!("{:>8}: {:.2}s", variant.name(), fastest.as_secs_f64());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,3 @@
+fn main() {
+    retrieval_build::generate().unwrap();
+}
@@ -0,0 +1,16 @@
+//! Sent before anything in lib.rs, as this file comes first alphabetically.
+
+use crate::*;
+
+#[send]
+impl Greeting {
+    const STR: &str = "Hello world!";
+}
+
+#[send]
+const _: Route = Route { path: "/greetings" };
+
+#[send(for T in [u8, u16])]
+impl Greeting {
+    const STR: &str = stringify!(T);
+}
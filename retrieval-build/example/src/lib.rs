@@ -0,0 +1,76 @@
+//! Sends implementations whose indices are assigned by the build script.
+
+use retrieval::*;
+
+pub mod greetings;
+
+#[retrieve(collect_consts)]
+pub trait Greeting {
+    const STR: &str = "";
+    fn call() {}
+}
+
+#[retrieve(collect_consts)]
+pub trait Shout {
+    const STR: &str = "";
+}
+
+/// Never sent, so it has no capacity at all.
+#[retrieve(collect_consts)]
+pub trait Unused {
+    const STR: &str = "";
+}
+
+/// Sent implementations behind cfgs, some of which the build script can't evaluate.
+#[retrieve(collect_consts)]
+pub trait Conditional {
+    const STR: &str = "";
+}
+
+#[retrieve]
+pub struct Route {
+    pub path: &'static str,
+}
+
+#[send(also = [Shout])]
+impl Greeting {
+    const STR: &str = "Hello again!";
+}
+
+#[send]
+const _: Route = Route { path: "/" };
+
+mod nested {
+    use super::*;
+
+    #[send(Greeting, const STR: &str = "Goodbye!")]
+    fn goodbye() {}
+}
+
+#[send]
+impl Conditional {
+    const STR: &str = "first";
+}
+
+// Only compiled for the crate's own tests, so it is indexed last.
+#[cfg(test)]
+#[send]
+impl Conditional {
+    const STR: &str = "tested";
+}
+
+#[cfg(any())]
+mod never {
+    use super::*;
+
+    #[send]
+    impl Conditional {
+        const STR: &str = "never";
+    }
+}
+
+#[cfg(not(any()))]
+#[send]
+impl Conditional {
+    const STR: &str = "second";
+}
@@ -0,0 +1,15 @@
+//! Tests that scanned retrieval traits default to a capacity of exactly their quantity.
+
+use retrieval_build_example::{Conditional, Greeting, Shout, Unused};
+
+const _: () = assert!(Greeting::__internal::CAPACITY == 5);
+const _: () = assert!(Shout::__internal::CAPACITY == 1);
+const _: () = assert!(Unused::__internal::CAPACITY == 0);
+// Leaves room for the send only compiled for the crate's own tests.
+const _: () = assert!(Conditional::__internal::CAPACITY == 3);
+
+#[test]
+fn main() {
+    assert_eq!(Greeting::ALL_STR.len(), Greeting::QUANTITY as usize);
+    assert!(Unused::ALL_STR.is_empty());
+}
@@ -0,0 +1,37 @@
+//! Implementations are indexed in the order they are written, going through files alphabetically.
+
+use retrieval::*;
+use retrieval_build_example::{Conditional, Greeting, Route, Shout};
+
+const _: () = assert!(Greeting::QUANTITY == 5);
+const _: () = assert!(Shout::QUANTITY == 1);
+const _: () = assert!(Conditional::QUANTITY == 2);
+const _: () = assert!(Route::QUANTITY == 2);
+
+// Greeting is from another crate, which iterate and for_each reach without being given a capacity.
+const LENGTH: usize = {
+    let mut length = 0;
+    for_each!(Greeting, |T| length += T::STR.len());
+    length
+};
+const _: () = assert!(LENGTH == 37);
+
+#[iterate(order = forward)]
+fn collect<T: Greeting>(strs: &mut Vec<&'static str>) {
+    strs.push(T::STR);
+}
+
+#[test]
+fn main() {
+    let mut strs = vec![];
+    collect(&mut strs);
+    assert_eq!(strs, Greeting::ALL_STR);
+
+    assert_eq!(
+        Greeting::ALL_STR,
+        ["Hello world!", "u8", "u16", "Hello again!", "Goodbye!"]
+    );
+    assert_eq!(Shout::ALL_STR, ["Hello again!"]);
+    assert_eq!(Conditional::ALL_STR, ["first", "second"]);
+    assert_eq!(Route::ALL.map(|route| route.path), ["/greetings", "/"]);
+}
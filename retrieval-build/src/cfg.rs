@@ -0,0 +1,133 @@
+//! Evaluates `cfg` predicates, as far as a build script can tell.
+
+use std::collections::{BTreeMap, BTreeSet};
+use syn::{Meta, Token, punctuated::Punctuated};
+
+/// Whether a predicate holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Truth {
+    True,
+    False,
+    /// The build script is shared by builds where it holds and builds where it doesn't.
+    Unknown,
+}
+
+/// Options that differ between builds sharing one build script run, such as the crate's tests.
+const UNKNOWABLE: [&str; 3] = ["test", "doc", "doctest"];
+
+/// The features and cfg options that the crate is being built with.
+#[derive(Debug, Clone, Default)]
+pub struct Cfgs {
+    /// Uppercase, with dashes replaced by underscores, as cargo gives them.
+    features: BTreeSet<String>,
+    /// Every value of each option. Options without a value have none.
+    options: BTreeMap<String, Vec<String>>,
+}
+
+/// How cargo names a feature in the environment.
+fn feature_name(name: &str) -> String {
+    name.to_uppercase().replace('-', "_")
+}
+
+impl Cfgs {
+    /// Reads the features and cfg options cargo gives to build scripts, from `CARGO_FEATURE_*` and `CARGO_CFG_*`.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let mut cfgs = Cfgs::default();
+        for (key, value) in std::env::vars() {
+            if let Some(feature) = key.strip_prefix("CARGO_FEATURE_") {
+                cfgs.features.insert(feature.to_string());
+            } else if let Some(option) = key.strip_prefix("CARGO_CFG_") {
+                cfgs.options.insert(
+                    option.to_lowercase(),
+                    value
+                        .split(',')
+                        .filter(|value| !value.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                );
+            }
+        }
+        cfgs
+    }
+
+    /// Enables a feature.
+    #[must_use]
+    pub fn feature(mut self, name: &str) -> Self {
+        self.features.insert(feature_name(name));
+        self
+    }
+
+    /// Sets a cfg option, such as `unix` or `target_os = "linux"`.
+    #[must_use]
+    pub fn option(mut self, name: &str, value: Option<&str>) -> Self {
+        let values = self.options.entry(name.to_string()).or_default();
+        values.extend(value.map(str::to_string));
+        self
+    }
+
+    /// Evaluates the predicate of a `cfg` or `cfg_attr`.\
+    /// Anything that isn't understood is unknown.
+    pub(crate) fn evaluate(&self, predicate: &Meta) -> Truth {
+        let name = predicate
+            .path()
+            .get_ident()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+
+        match predicate {
+            Meta::Path(_) if UNKNOWABLE.contains(&name.as_str()) => Truth::Unknown,
+            Meta::Path(_) => self.options.contains_key(&name).into(),
+            Meta::NameValue(predicate) => {
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(value),
+                    ..
+                }) = &predicate.value
+                else {
+                    return Truth::Unknown;
+                };
+                let value = value.value();
+                if name == "feature" {
+                    self.features.contains(&feature_name(&value)).into()
+                } else {
+                    self.options
+                        .get(&name)
+                        .is_some_and(|values| values.contains(&value))
+                        .into()
+                }
+            }
+            Meta::List(list) => {
+                let Ok(predicates) =
+                    list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                else {
+                    return Truth::Unknown;
+                };
+                let mut truths = predicates.iter().map(|predicate| self.evaluate(predicate));
+                match name.as_str() {
+                    "all" => truths.fold(Truth::True, |all, truth| match (all, truth) {
+                        (Truth::False, _) | (_, Truth::False) => Truth::False,
+                        (Truth::Unknown, _) | (_, Truth::Unknown) => Truth::Unknown,
+                        (Truth::True, Truth::True) => Truth::True,
+                    }),
+                    "any" => truths.fold(Truth::False, |any, truth| match (any, truth) {
+                        (Truth::True, _) | (_, Truth::True) => Truth::True,
+                        (Truth::Unknown, _) | (_, Truth::Unknown) => Truth::Unknown,
+                        (Truth::False, Truth::False) => Truth::False,
+                    }),
+                    "not" => match (truths.next(), truths.next()) {
+                        (Some(Truth::True), None) => Truth::False,
+                        (Some(Truth::False), None) => Truth::True,
+                        _ => Truth::Unknown,
+                    },
+                    _ => Truth::Unknown,
+                }
+            }
+        }
+    }
+}
+
+impl From<bool> for Truth {
+    fn from(value: bool) -> Self {
+        if value { Truth::True } else { Truth::False }
+    }
+}
@@ -0,0 +1,161 @@
+//! Assigns the index of every implementation sent for [retrieval](https://docs.rs/retrieval/) from a build script, instead of counting them during macro expansion.\
+//! The indices no longer depend on the order macros are expanded in, or on macro expansions being cached.
+//!
+//! Call [generate] from the main function of your build script:
+//! ```no_run
+//! retrieval_build::generate().unwrap();
+//! ```
+//! Every retrieval trait found in `src` then has its implementations indexed in the order they are written, going through files in alphabetical order.\
+//! Traits with `explicit`, and sends that give `index = N`, are left alone.\
+//! Items behind a `cfg` are evaluated against the crate's features and cfg options, and skipped if compiled out.\
+//! A build script can't tell whether `test` or `doc` hold, so sends behind them are indexed after every other send to their trait.\
+//! Scanned traits that aren't given a capacity get exactly their quantity, so they cost no more to compile than they need to.
+//!
+//! Sends must be written directly in a scanned file, so that they can be found. A send made by another macro is counted as usual, which doesn't mix with scanned sends.\
+//! A send finds its retrieval trait by the trait's id, which is its name unless `id = "..."` is given.\
+//! If several traits have the id, the one that the send's path leads to is used, or without a path, the one in the send's own module. Otherwise the send is ambiguous.\
+//! A send that can't be parsed is an error, as the indices after it would depend on it.
+#![deny(clippy::unwrap_used)]
+#![warn(clippy::pedantic)]
+
+mod cfg;
+mod registry;
+mod scan;
+
+pub use cfg::Cfgs;
+pub use registry::{Registry, Retrieved, Sent};
+
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+/// The environment variable holding the path to the generated registry.
+pub const ENV: &str = "RETRIEVAL_REGISTRY";
+
+/// Something that went wrong while generating or loading the registry.
+#[derive(Debug)]
+pub enum Error {
+    /// A file couldn't be read or written.
+    Io(PathBuf, std::io::Error),
+    /// A source file, or a send attribute in it, couldn't be parsed.
+    Parse(PathBuf, syn::Error),
+    /// A send's retrieval trait has the same id as more than one retrieval trait, and its path doesn't tell which.
+    Ambiguous {
+        file: PathBuf,
+        line: usize,
+        name: String,
+    },
+    /// A retrieval trait was sent more implementations than an index can hold.
+    TooMany(String),
+    /// A retrieval trait was sent implementations behind different predicates that can't be evaluated, so they can't all go last.
+    Conditions {
+        name: String,
+        first: String,
+        second: String,
+    },
+    /// The registry file was not in the expected format.
+    Malformed(PathBuf, usize),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(path, error) => write!(f, "Could not access {}: {error}", path.display()),
+            Error::Parse(path, error) => {
+                let start = error.span().start();
+                write!(
+                    f,
+                    "Could not parse {}:{}:{}: {error}",
+                    path.display(),
+                    start.line,
+                    start.column + 1,
+                )
+            }
+            Error::Ambiguous { file, line, name } => write!(
+                f,
+                "The send at {}:{line} could be to any of the retrieval traits with the id {name}. Write the path to the trait, or give them different ids.",
+                file.display(),
+            ),
+            Error::TooMany(name) => write!(
+                f,
+                "The retrieval trait {name} was sent more implementations than a u16 can index."
+            ),
+            Error::Conditions {
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "The retrieval trait {name} is sent implementations behind both cfg({first}) and cfg({second}), which a build script can't evaluate. Put them behind the same cfg."
+            ),
+            Error::Malformed(path, line) => write!(
+                f,
+                "The registry {} is malformed at line {line}. Try cleaning and building again.",
+                path.display(),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Scans the `src` directory, and makes the assigned indices available to the retrieval macros.\
+/// Must be called from a build script.
+///
+/// # Errors
+/// Fails if a file can't be read or parsed, if the sends can't be indexed, or if the registry can't be written.
+pub fn generate() -> Result<(), Error> {
+    generate_in(&["src"])
+}
+
+/// Scans every `.rs` file in the given directories or files, relative to the package, and makes the assigned indices available to the retrieval macros.\
+/// Must be called from a build script.
+///
+/// # Errors
+/// See [generate].
+pub fn generate_in<P: AsRef<Path>>(paths: &[P]) -> Result<(), Error> {
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap_or_default());
+
+    let mut files = vec![];
+    for path in paths {
+        let path = manifest_dir.join(path);
+        println!("cargo::rerun-if-changed={}", path.display());
+        rust_files(&path, &mut files)?;
+    }
+    files.sort();
+    files.dedup();
+
+    let sources = files
+        .into_iter()
+        .map(|file| {
+            let source =
+                std::fs::read_to_string(&file).map_err(|error| Error::Io(file.clone(), error))?;
+            Ok((file, source))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let registry = Registry::scan(&sources, &Cfgs::from_env())?;
+
+    let registry_path = out_dir.join("retrieval_registry.txt");
+    std::fs::write(&registry_path, registry.to_string())
+        .map_err(|error| Error::Io(registry_path.clone(), error))?;
+    println!("cargo::rustc-env={ENV}={}", registry_path.display());
+
+    Ok(())
+}
+
+/// Finds every `.rs` file at the path, canonicalised so that they can be compared with where macros are called from.
+fn rust_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let io = |error| Error::Io(path.to_path_buf(), error);
+
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path).map_err(io)? {
+            rust_files(&entry.map_err(io)?.path(), files)?;
+        }
+    } else if path.extension().is_some_and(|extension| extension == "rs") {
+        files.push(path.canonicalize().map_err(io)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+//! The registry of assigned indices, and the file format it is passed to the retrieval macros in.
+
+use crate::{ENV, Error};
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+/// A retrieval trait found by the scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retrieved {
+    /// The file containing the retrieve attribute.
+    pub file: PathBuf,
+    /// The line of the retrieve attribute.
+    pub line: usize,
+    /// The trait's name. Types whose values are retrieved use the name of their hidden trait.
+    pub name: String,
+    /// The amount of implementations sent to it.
+    pub quantity: u16,
+}
+
+/// An implementation sent to a scanned retrieval trait.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sent {
+    /// The file containing the send attribute.
+    pub file: PathBuf,
+    /// The line of the send attribute.
+    pub line: usize,
+    /// Which copy this is, when the send starts with `for Ident in [Type, ...]`. Otherwise 0.
+    pub copy: usize,
+    /// The name of the retrieval trait it was sent to.
+    pub name: String,
+    /// The assigned index, starting from 1.
+    pub index: u16,
+}
+
+/// Every scanned retrieval trait, and the index assigned to each implementation sent to them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Registry {
+    pub retrieved: Vec<Retrieved>,
+    pub sent: Vec<Sent>,
+}
+
+impl Registry {
+    /// Loads the registry generated by the build script, if there is one.
+    ///
+    /// # Errors
+    /// Fails if the registry can't be read, or is malformed.
+    pub fn load() -> Result<Option<Self>, Error> {
+        let Some(path) = std::env::var_os(ENV) else {
+            return Ok(None);
+        };
+        Self::read(&PathBuf::from(path)).map(Some)
+    }
+
+    /// Reads the registry at the path.
+    ///
+    /// # Errors
+    /// Fails if the registry can't be read, or is malformed.
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let registry =
+            std::fs::read_to_string(path).map_err(|error| Error::Io(path.to_path_buf(), error))?;
+        Self::parse(&registry, path)
+    }
+
+    /// The quantity of the retrieval trait whose retrieve attribute is at the location, if it was scanned.
+    #[must_use]
+    pub fn quantity(&self, file: &Path, line: usize) -> Option<u16> {
+        self.retrieved
+            .iter()
+            .find(|retrieved| retrieved.line == line && retrieved.file == file)
+            .map(|retrieved| retrieved.quantity)
+    }
+
+    /// The index assigned to the copy of the send at the location, for the named retrieval trait.
+    #[must_use]
+    pub fn index(&self, file: &Path, line: usize, copy: usize, name: &str) -> Option<u16> {
+        self.sent
+            .iter()
+            .find(|sent| {
+                sent.line == line && sent.copy == copy && sent.name == name && sent.file == file
+            })
+            .map(|sent| sent.index)
+    }
+
+    /// Reads the registry back from the format it is displayed in.\
+    /// The path is only used for errors.
+    fn parse(registry: &str, path: &Path) -> Result<Self, Error> {
+        let mut parsed = Registry::default();
+
+        for (line_number, line) in registry.lines().enumerate() {
+            let malformed = || Error::Malformed(path.to_path_buf(), line_number + 1);
+            let mut fields = line.splitn(6, '\t');
+            let mut field = || fields.next().ok_or_else(malformed);
+
+            match field()? {
+                "retrieve" => {
+                    let line = field()?.parse().map_err(|_| malformed())?;
+                    let name = field()?.to_string();
+                    let quantity = field()?.parse().map_err(|_| malformed())?;
+                    let file = PathBuf::from(field()?);
+                    parsed.retrieved.push(Retrieved {
+                        file,
+                        line,
+                        name,
+                        quantity,
+                    });
+                }
+                "send" => {
+                    let line = field()?.parse().map_err(|_| malformed())?;
+                    let copy = field()?.parse().map_err(|_| malformed())?;
+                    let name = field()?.to_string();
+                    let index = field()?.parse().map_err(|_| malformed())?;
+                    let file = PathBuf::from(field()?);
+                    parsed.sent.push(Sent {
+                        file,
+                        line,
+                        copy,
+                        name,
+                        index,
+                    });
+                }
+                _ => return Err(malformed()),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// One entry per line, with tab separated fields. The file comes last, so that it can contain anything but a newline.
+impl Display for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for Retrieved {
+            file,
+            line,
+            name,
+            quantity,
+        } in &self.retrieved
+        {
+            writeln!(
+                f,
+                "retrieve\t{line}\t{name}\t{quantity}\t{}",
+                file.display()
+            )?;
+        }
+        for Sent {
+            file,
+            line,
+            copy,
+            name,
+            index,
+        } in &self.sent
+        {
+            writeln!(
+                f,
+                "send\t{line}\t{copy}\t{name}\t{index}\t{}",
+                file.display()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,756 @@
+//! Finds every retrieve and send attribute in the source files, and assigns indices to the sends.
+
+use crate::{
+    Error, Registry, Retrieved, Sent,
+    cfg::{Cfgs, Truth},
+};
+use quote::ToTokens;
+use std::path::{Path as FilePath, PathBuf};
+use syn::{
+    Attribute, Ident, Item, ItemConst, ItemEnum, ItemFn, ItemImpl, ItemMod, ItemStruct, ItemTrait,
+    LitInt, LitStr, Meta, Path, Token, Type,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    visit::{self, Visit},
+};
+
+/// A retrieve attribute, on a trait or on a type whose values are retrieved.
+struct FoundRetrieve {
+    file: usize,
+    line: usize,
+    name: String,
+    /// The id that sends give, which is the name unless `id = "..."` is given.
+    id: String,
+    /// The path of the module it is in, from the crate root.
+    modules: Vec<String>,
+    /// Explicit traits are given their indices by hand.
+    explicit: bool,
+    condition: Vec<String>,
+}
+
+/// A retrieval trait that a send sends to.
+struct Target {
+    /// The trait's name as written, which the send macro looks its index up by.
+    name: String,
+    /// The id given, or the name.
+    id: String,
+    /// The modules the trait's path is written through.
+    modules: Vec<String>,
+}
+
+/// A send attribute, along with every retrieval trait it sends to.
+struct FoundSend {
+    file: usize,
+    line: usize,
+    copies: usize,
+    targets: Vec<Target>,
+    /// The path of the module it is in, from the crate root.
+    modules: Vec<String>,
+    /// Sends given `index = N` are left alone.
+    explicit: bool,
+    condition: Vec<String>,
+}
+
+/// A module whose contents are in another file, and where that file could be.
+struct Declared {
+    file: usize,
+    /// The module's path within the declaring file.
+    modules: Vec<String>,
+    candidates: Vec<PathBuf>,
+    /// None if the module is compiled out.
+    condition: Option<Vec<String>>,
+}
+
+/// An attribute, found directly or through a `cfg_attr` that may apply.
+struct Found {
+    line: usize,
+    meta: Meta,
+    condition: Vec<String>,
+}
+
+/// The parts of a send attribute that decide where it is sent, and how many times.\
+/// Anything it doesn't understand is left for the send macro to complain about.
+struct SendArguments {
+    copies: usize,
+    trait_path: Option<Path>,
+    id: Option<String>,
+    /// Each other trait, with its own id.
+    also: Vec<(Path, Option<String>)>,
+    index: bool,
+}
+
+impl Parse for SendArguments {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut arguments = SendArguments {
+            copies: 1,
+            trait_path: None,
+            id: None,
+            also: vec![],
+            index: false,
+        };
+
+        if input.parse::<Option<Token![for]>>()?.is_some() {
+            input.parse::<Ident>()?;
+            input.parse::<Token![in]>()?;
+            let types;
+            syn::bracketed!(types in input);
+            arguments.copies = Punctuated::<Type, Token![,]>::parse_terminated(&types)?.len();
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        while !input.is_empty() {
+            if input.peek(Token![const]) {
+                input.parse::<Token![const]>()?;
+                input.parse::<Ident>()?;
+                input.parse::<Token![:]>()?;
+                input.parse::<Type>()?;
+                input.parse::<Token![=]>()?;
+                input.parse::<syn::Expr>()?;
+            } else if input.peek(Token![fn]) {
+                input.parse::<Token![fn]>()?;
+                input.parse::<Token![=]>()?;
+                input.parse::<Ident>()?;
+            } else if input.peek(Ident) && input.peek2(Token![=]) {
+                let ident = input.parse::<Ident>()?;
+                input.parse::<Token![=]>()?;
+                if ident == "also" {
+                    let targets;
+                    syn::bracketed!(targets in input);
+                    while !targets.is_empty() {
+                        let target = Path::parse_mod_style(&targets)?;
+                        // A target given its own index is left alone.
+                        let (mut explicit, mut id) = (false, None);
+                        if targets.peek(syn::token::Paren) {
+                            let options;
+                            syn::parenthesized!(options in targets);
+                            let options = options.parse::<SendArguments>()?;
+                            (explicit, id) = (options.index, options.id);
+                        }
+                        if !explicit {
+                            arguments.also.push((target, id));
+                        }
+                        // The items sent don't affect the index.
+                        if targets.peek(syn::token::Brace) {
+                            targets.parse::<proc_macro2::Group>()?;
+                        }
+                        if !targets.is_empty() {
+                            targets.parse::<Token![,]>()?;
+                        }
+                    }
+                } else if ident == "index" {
+                    input.parse::<LitInt>()?;
+                    arguments.index = true;
+                } else if ident == "id" {
+                    arguments.id = Some(input.parse::<LitStr>()?.value());
+                } else {
+                    input.parse::<syn::Expr>()?;
+                }
+            } else {
+                arguments.trait_path = Some(input.parse()?);
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(arguments)
+    }
+}
+
+/// Visits every item in a file, including those in modules and function bodies, in the order they are written.\
+/// Items that are compiled out are skipped.
+struct Scanner<'a> {
+    file: usize,
+    path: &'a FilePath,
+    cfgs: &'a Cfgs,
+    /// The predicates that the current item is behind, which can't be evaluated.
+    condition: Vec<String>,
+    /// The inline modules the current item is in.
+    modules: Vec<String>,
+    retrieves: &'a mut Vec<FoundRetrieve>,
+    sends: &'a mut Vec<FoundSend>,
+    declared: &'a mut Vec<Declared>,
+    /// Send attributes that couldn't be parsed, so their indices would be unknown.
+    errors: &'a mut Vec<syn::Error>,
+}
+
+/// Does the attribute have the given name, whatever path it was used through?
+fn named(meta: &Meta, name: &str) -> bool {
+    meta.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == name)
+}
+
+/// The attributes of any item that has them.
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Const(item) => &item.attrs,
+        Item::Enum(item) => &item.attrs,
+        Item::ExternCrate(item) => &item.attrs,
+        Item::Fn(item) => &item.attrs,
+        Item::ForeignMod(item) => &item.attrs,
+        Item::Impl(item) => &item.attrs,
+        Item::Macro(item) => &item.attrs,
+        Item::Mod(item) => &item.attrs,
+        Item::Static(item) => &item.attrs,
+        Item::Struct(item) => &item.attrs,
+        Item::Trait(item) => &item.attrs,
+        Item::TraitAlias(item) => &item.attrs,
+        Item::Type(item) => &item.attrs,
+        Item::Union(item) => &item.attrs,
+        Item::Use(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+/// The modules a path is written through, and the name it ends with.
+fn split(path: &Path) -> Option<(Vec<String>, String)> {
+    let mut segments: Vec<_> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let name = segments.pop()?;
+    Some((segments, name))
+}
+
+fn type_path(ty: &Type) -> Option<&Path> {
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => Some(&ty.path),
+        _ => None,
+    }
+}
+
+/// The name of the hidden retrieval trait that holds a type's values.
+fn registry_name(ident: &str) -> String {
+    format!("__retrieval_{ident}")
+}
+
+impl Scanner<'_> {
+    /// The predicates an item with the attributes is behind, or None if it is compiled out.
+    fn condition(&self, attrs: &[Attribute]) -> Option<Vec<String>> {
+        let mut condition = self.condition.clone();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("cfg")) {
+            let predicate = attr.parse_args::<Meta>();
+            match predicate
+                .as_ref()
+                .map(|predicate| self.cfgs.evaluate(predicate))
+            {
+                Ok(Truth::True) => {}
+                Ok(Truth::False) => return None,
+                Ok(Truth::Unknown) | Err(_) => condition.push(match &attr.meta {
+                    Meta::List(list) => list.tokens.to_string(),
+                    meta => meta.to_token_stream().to_string(),
+                }),
+            }
+        }
+        Some(condition)
+    }
+
+    /// Finds the attribute with the given name, including through any `cfg_attr` that may apply.
+    fn find(&self, attrs: &[Attribute], name: &str) -> Option<Found> {
+        attrs.iter().find_map(|attr| {
+            let (meta, condition) = self.find_in(&attr.meta, name, self.condition.clone())?;
+            Some(Found {
+                line: attr.pound_token.span.start().line,
+                meta,
+                condition,
+            })
+        })
+    }
+
+    fn find_in(
+        &self,
+        meta: &Meta,
+        name: &str,
+        mut condition: Vec<String>,
+    ) -> Option<(Meta, Vec<String>)> {
+        if named(meta, name) {
+            return Some((meta.clone(), condition));
+        }
+        let Meta::List(list) = meta else {
+            return None;
+        };
+        if !list.path.is_ident("cfg_attr") {
+            return None;
+        }
+
+        let mut metas = list
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            .ok()?
+            .into_iter();
+        let predicate = metas.next()?;
+        match self.cfgs.evaluate(&predicate) {
+            Truth::True => {}
+            Truth::False => return None,
+            Truth::Unknown => condition.push(predicate.to_token_stream().to_string()),
+        }
+        metas.find_map(|meta| self.find_in(&meta, name, condition.clone()))
+    }
+
+    /// Records where a module in another file could be, so that its file can be given the module's condition.
+    fn declare(&mut self, item: &ItemMod, condition: Option<Vec<String>>) {
+        let parent = self.path.parent().unwrap_or(FilePath::new(""));
+        let mod_rs = self
+            .path
+            .file_name()
+            .is_some_and(|name| name == "lib.rs" || name == "main.rs" || name == "mod.rs");
+        let mut directory = if mod_rs {
+            parent.to_path_buf()
+        } else {
+            parent.join(self.path.file_stem().unwrap_or_default())
+        };
+        directory.extend(&self.modules);
+
+        let path = item.attrs.iter().find_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("path") => match &meta.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(path),
+                    ..
+                }) => Some(path.value()),
+                _ => None,
+            },
+            _ => None,
+        });
+        let ident = item.ident.to_string();
+        let candidates = match path {
+            Some(path) if self.modules.is_empty() => vec![parent.join(path)],
+            Some(path) => vec![directory.join(path)],
+            None => vec![
+                directory.join(format!("{ident}.rs")),
+                directory.join(&ident).join("mod.rs"),
+            ],
+        };
+
+        self.declared.push(Declared {
+            file: self.file,
+            modules: self.modules.iter().cloned().chain([ident]).collect(),
+            candidates,
+            condition,
+        });
+    }
+
+    fn retrieve(&mut self, attrs: &[Attribute], name: String) {
+        let Some(attr) = self.find(attrs, "retrieve") else {
+            return;
+        };
+        let tokens: Vec<_> = match &attr.meta {
+            syn::Meta::List(list) => list.tokens.clone().into_iter().collect(),
+            _ => vec![],
+        };
+        let explicit = tokens.iter().any(
+            |token| matches!(token, proc_macro2::TokenTree::Ident(ident) if ident == "explicit"),
+        );
+        let id = tokens.windows(3).find_map(|window| match window {
+            [
+                proc_macro2::TokenTree::Ident(ident),
+                proc_macro2::TokenTree::Punct(punct),
+                proc_macro2::TokenTree::Literal(literal),
+            ] if ident == "id" && punct.as_char() == '=' => {
+                syn::parse2::<LitStr>(literal.to_token_stream())
+                    .ok()
+                    .map(|id| id.value())
+            }
+            _ => None,
+        });
+
+        self.retrieves.push(FoundRetrieve {
+            file: self.file,
+            line: attr.line,
+            id: id.unwrap_or_else(|| name.clone()),
+            name,
+            modules: self.modules.clone(),
+            explicit,
+            condition: attr.condition,
+        });
+    }
+
+    /// Records a send, given the path of the retrieval trait it is sent to when the attribute doesn't say.\
+    /// A send that can't be parsed is recorded as an error, as the indices after it would depend on it.
+    fn send(&mut self, attrs: &[Attribute], written: Option<(Vec<String>, String)>) {
+        let Some(attr) = self.find(attrs, "send") else {
+            return;
+        };
+        let arguments = match &attr.meta {
+            syn::Meta::Path(_) => Ok(SendArguments {
+                copies: 1,
+                trait_path: None,
+                id: None,
+                also: vec![],
+                index: false,
+            }),
+            syn::Meta::List(list) => list.parse_args::<SendArguments>(),
+            syn::Meta::NameValue(meta) => Err(syn::Error::new_spanned(
+                meta,
+                "A send takes its options in parentheses.",
+            )),
+        };
+        let arguments = match arguments {
+            Ok(arguments) => arguments,
+            Err(error) => {
+                self.errors.push(error);
+                return;
+            }
+        };
+
+        let target = |(modules, name): (Vec<String>, String), id: Option<String>| Target {
+            id: id.unwrap_or_else(|| name.clone()),
+            name,
+            modules,
+        };
+        let targets = arguments
+            .trait_path
+            .as_ref()
+            .and_then(split)
+            .or(written)
+            .map(|written| target(written, arguments.id))
+            .into_iter()
+            .chain(
+                arguments
+                    .also
+                    .into_iter()
+                    .filter_map(|(path, id)| Some(target(split(&path)?, id))),
+            )
+            .collect();
+
+        self.sends.push(FoundSend {
+            file: self.file,
+            line: attr.line,
+            copies: arguments.copies,
+            targets,
+            modules: self.modules.clone(),
+            explicit: arguments.index,
+            condition: attr.condition,
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for Scanner<'_> {
+    fn visit_item(&mut self, item: &'ast Item) {
+        let Some(condition) = self.condition(item_attrs(item)) else {
+            if let Item::Mod(item) = item
+                && item.content.is_none()
+            {
+                self.declare(item, None);
+            }
+            return;
+        };
+        let outer = std::mem::replace(&mut self.condition, condition);
+        visit::visit_item(self, item);
+        self.condition = outer;
+    }
+
+    fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+        if item.content.is_none() {
+            self.declare(item, Some(self.condition.clone()));
+        }
+        self.modules.push(item.ident.to_string());
+        visit::visit_item_mod(self, item);
+        self.modules.pop();
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast ItemTrait) {
+        self.retrieve(&item.attrs, item.ident.to_string());
+        visit::visit_item_trait(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast ItemStruct) {
+        self.retrieve(&item.attrs, registry_name(&item.ident.to_string()));
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast ItemEnum) {
+        self.retrieve(&item.attrs, registry_name(&item.ident.to_string()));
+        visit::visit_item_enum(self, item);
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        let path = match &item.trait_ {
+            Some((_, trait_path, _)) => Some(trait_path),
+            None => type_path(&item.self_ty),
+        };
+        self.send(&item.attrs, path.and_then(split));
+        visit::visit_item_impl(self, item);
+    }
+
+    fn visit_item_const(&mut self, item: &'ast ItemConst) {
+        let written = type_path(&item.ty).and_then(split);
+        self.send(
+            &item.attrs,
+            written.map(|(modules, name)| (modules, registry_name(&name))),
+        );
+        visit::visit_item_const(self, item);
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.send(&item.attrs, None);
+        visit::visit_item_fn(self, item);
+    }
+}
+
+/// The modules that a path written through the given modules could lead to, from inside the given module.\
+/// A path that doesn't start from `crate`, `self` or `super` may also lead from the crate root, such as through a use.
+fn led_to(from: &[String], written: &[String]) -> Vec<Vec<String>> {
+    let mut module = from.to_vec();
+    match written.first().map(String::as_str) {
+        Some("crate") => vec![written[1..].to_vec()],
+        Some("self") => {
+            module.extend_from_slice(&written[1..]);
+            vec![module]
+        }
+        Some("super") => {
+            let supers = written
+                .iter()
+                .take_while(|segment| *segment == "super")
+                .count();
+            if supers > module.len() {
+                return vec![];
+            }
+            module.truncate(module.len() - supers);
+            module.extend_from_slice(&written[supers..]);
+            vec![module]
+        }
+        _ => {
+            module.extend_from_slice(written);
+            vec![module, written.to_vec()]
+        }
+    }
+}
+
+/// Finds the retrieval trait a send is sent to by its id.\
+/// If several have the id, the one that the trait's path leads to is used, otherwise the one in the send's own module.
+fn resolve(
+    retrieves: &[FoundRetrieve],
+    send: &FoundSend,
+    target: &Target,
+    file: &std::path::Path,
+) -> Result<Option<usize>, Error> {
+    let with_id: Vec<_> = retrieves
+        .iter()
+        .enumerate()
+        .filter(|(_, retrieve)| retrieve.id == target.id)
+        .collect();
+    let only = |found: &dyn Fn(&FoundRetrieve) -> bool| {
+        let mut found = with_id.iter().filter(|(_, retrieve)| found(retrieve));
+        match (found.next(), found.next()) {
+            (Some((index, _)), None) => Some(*index),
+            _ => None,
+        }
+    };
+
+    let led_to = led_to(&send.modules, &target.modules);
+    let found = match (with_id.as_slice(), target.modules.is_empty()) {
+        ([], _) => return Ok(None),
+        ([(index, _)], _) => Some(*index),
+        (_, false) => only(&|retrieve| led_to.contains(&retrieve.modules)),
+        (_, true) => {
+            only(&|retrieve| retrieve.file == send.file && retrieve.modules == send.modules)
+        }
+    };
+
+    found.map(Some).ok_or_else(|| Error::Ambiguous {
+        file: file.to_path_buf(),
+        line: send.line,
+        name: target.id.clone(),
+    })
+}
+
+/// The path of the module a file holds, through the modules that include it.
+fn file_modules(
+    file: usize,
+    sources: &[(PathBuf, String)],
+    declared: &[Declared],
+    depth: usize,
+) -> Vec<String> {
+    let path = &sources[file].0;
+    let Some(declaration) = declared
+        .iter()
+        .find(|declaration| declaration.candidates.contains(path))
+    else {
+        return vec![];
+    };
+    if depth == 0 {
+        return vec![];
+    }
+
+    let mut modules = file_modules(declaration.file, sources, declared, depth - 1);
+    modules.extend(declaration.modules.iter().cloned());
+    modules
+}
+
+/// The predicates a file is behind, through the modules that include it, or None if it is compiled out.\
+/// Files that no scanned module includes, such as the crate root, are always compiled.
+fn file_condition(
+    file: usize,
+    sources: &[(PathBuf, String)],
+    declared: &[Declared],
+    depth: usize,
+) -> Option<Vec<String>> {
+    let path = &sources[file].0;
+    let Some(declaration) = declared
+        .iter()
+        .find(|declaration| declaration.candidates.contains(path))
+    else {
+        return Some(vec![]);
+    };
+    // Modules that include each other can't be compiled anyway.
+    if depth == 0 {
+        return Some(vec![]);
+    }
+
+    let mut condition = file_condition(declaration.file, sources, declared, depth - 1)?;
+    condition.extend(declaration.condition.clone()?);
+    Some(condition)
+}
+
+/// Writes predicates the way a single cfg would take them.
+fn predicate(condition: &[String]) -> String {
+    match condition {
+        [predicate] => predicate.clone(),
+        condition => format!("all({})", condition.join(", ")),
+    }
+}
+
+/// Finds every retrieve and send that may be compiled, along with the predicates they are behind, including through the modules that include their files.
+fn find(
+    sources: &[(PathBuf, String)],
+    cfgs: &Cfgs,
+) -> Result<(Vec<FoundRetrieve>, Vec<FoundSend>), Error> {
+    let mut retrieves = vec![];
+    let mut sends = vec![];
+    let mut declared = vec![];
+    let mut errors = vec![];
+    for (file, (path, source)) in sources.iter().enumerate() {
+        let parsed = syn::parse_file(source).map_err(|error| Error::Parse(path.clone(), error))?;
+        Scanner {
+            file,
+            path,
+            cfgs,
+            condition: vec![],
+            modules: vec![],
+            retrieves: &mut retrieves,
+            sends: &mut sends,
+            declared: &mut declared,
+            errors: &mut errors,
+        }
+        .visit_file(&parsed);
+        if let Some(error) = errors.drain(..).next() {
+            return Err(Error::Parse(path.clone(), error));
+        }
+    }
+
+    let files: Vec<_> = (0..sources.len())
+        .map(|file| file_condition(file, sources, &declared, sources.len()))
+        .collect();
+    let modules: Vec<_> = (0..sources.len())
+        .map(|file| file_modules(file, sources, &declared, sources.len()))
+        .collect();
+    let in_file = |file: usize, condition: Vec<String>| {
+        let mut in_file = files[file].clone()?;
+        in_file.extend(condition);
+        Some(in_file)
+    };
+    let retrieves: Vec<_> = retrieves
+        .into_iter()
+        .filter_map(|retrieve| {
+            Some(FoundRetrieve {
+                condition: in_file(retrieve.file, retrieve.condition)?,
+                modules: [modules[retrieve.file].clone(), retrieve.modules].concat(),
+                ..retrieve
+            })
+        })
+        .collect();
+    let sends: Vec<_> = sends
+        .into_iter()
+        .filter_map(|send| {
+            Some(FoundSend {
+                condition: in_file(send.file, send.condition)?,
+                modules: [modules[send.file].clone(), send.modules].concat(),
+                ..send
+            })
+        })
+        .collect();
+
+    Ok((retrieves, sends))
+}
+
+impl Registry {
+    /// Scans the source files, in the order given, and assigns an index to every implementation sent to a retrieval trait found in them.\
+    /// Sends are indexed in the order they are written. Each copy of a template send is indexed in turn, with each copy's other traits after its main trait.
+    ///
+    /// Items behind a `cfg` or `cfg_attr` are evaluated against the cfgs given, and skipped if they are compiled out.\
+    /// Sends behind predicates that can't be evaluated, such as `test`, are indexed after every other send to their retrieval trait,
+    /// so that the indices before them are the same whether or not they are compiled.
+    ///
+    /// # Errors
+    /// Fails if a file can't be parsed, if a send's retrieval trait is ambiguous, if a trait is sent too many implementations,
+    /// or if a trait is sent implementations behind different predicates that can't be evaluated.
+    pub fn scan(sources: &[(PathBuf, String)], cfgs: &Cfgs) -> Result<Self, Error> {
+        let (retrieves, sends) = find(sources, cfgs)?;
+
+        let mut registry = Registry::default();
+        let mut quantities = vec![0_u16; retrieves.len()];
+        // The predicates of the first send that couldn't be evaluated, for each trait.
+        let mut conditions: Vec<Option<&[String]>> = vec![None; retrieves.len()];
+        let unconditional = sends.iter().filter(|send| send.condition.is_empty());
+        let conditional = sends.iter().filter(|send| !send.condition.is_empty());
+        for send in unconditional
+            .chain(conditional)
+            .filter(|send| !send.explicit)
+        {
+            let file = &sources[send.file].0;
+            for copy in 0..send.copies {
+                for target in &send.targets {
+                    let name = &target.name;
+                    let Some(retrieve) = resolve(&retrieves, send, target, file)? else {
+                        continue;
+                    };
+                    if retrieves[retrieve].explicit {
+                        continue;
+                    }
+
+                    if !send.condition.is_empty() {
+                        let first = conditions[retrieve].get_or_insert(&send.condition);
+                        if **first != send.condition {
+                            return Err(Error::Conditions {
+                                name: name.clone(),
+                                first: predicate(first),
+                                second: predicate(&send.condition),
+                            });
+                        }
+                    }
+
+                    let quantity = &mut quantities[retrieve];
+                    *quantity = quantity
+                        .checked_add(1)
+                        .ok_or_else(|| Error::TooMany(name.clone()))?;
+                    registry.sent.push(Sent {
+                        file: file.clone(),
+                        line: send.line,
+                        copy,
+                        name: name.clone(),
+                        index: *quantity,
+                    });
+                }
+            }
+        }
+
+        registry.retrieved = retrieves
+            .into_iter()
+            .zip(quantities)
+            .filter(|(retrieve, _)| !retrieve.explicit)
+            .map(|(retrieve, quantity)| Retrieved {
+                file: sources[retrieve.file].0.clone(),
+                line: retrieve.line,
+                name: retrieve.name,
+                quantity,
+            })
+            .collect();
+
+        Ok(registry)
+    }
+}
@@ -0,0 +1,293 @@
+//! Sends are indexed in the order they are written, going through files in the order given.\
+//! Compiled out sends are skipped, and sends behind a cfg that can't be evaluated go last.
+
+use retrieval_build::{Cfgs, Error, Registry, Retrieved, Sent};
+use std::path::PathBuf;
+
+fn sources(files: &[(&str, &str)]) -> Vec<(PathBuf, String)> {
+    files
+        .iter()
+        .map(|(file, source)| (PathBuf::from(file), (*source).to_string()))
+        .collect()
+}
+
+fn sent(file: &str, line: usize, copy: usize, name: &str, index: u16) -> Sent {
+    Sent {
+        file: PathBuf::from(file),
+        line,
+        copy,
+        name: name.to_string(),
+        index,
+    }
+}
+
+#[test]
+fn main() {
+    let registry = Registry::scan(
+        &sources(&[
+            (
+                "a.rs",
+                r#"
+#[send(for T in [u8, u16], also = [Shout { STR }, Manual(index = 2)])]
+impl Message {}
+
+#[send(index = 1)]
+impl Manual {}
+
+mod inner {
+    #[retrieval::send(Message, fn = run)]
+    fn run() {}
+}
+"#,
+            ),
+            (
+                "b.rs",
+                r#"
+#[retrieve(10)]
+trait Message {}
+
+#[retrieve(explicit)]
+trait Manual {}
+
+#[retrieve]
+trait Shout {}
+
+#[retrieve]
+struct Route;
+
+#[send]
+impl Message for Named {}
+
+#[send]
+const _: Route = Route;
+
+#[send]
+impl Unknown {}
+"#,
+            ),
+        ]),
+        &Cfgs::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        registry.retrieved,
+        [
+            ("Message", 2, 4),
+            ("Shout", 8, 2),
+            ("__retrieval_Route", 11, 1),
+        ]
+        .map(|(name, line, quantity)| Retrieved {
+            file: PathBuf::from("b.rs"),
+            line,
+            name: name.to_string(),
+            quantity,
+        })
+    );
+    assert_eq!(
+        registry.sent,
+        [
+            sent("a.rs", 2, 0, "Message", 1),
+            sent("a.rs", 2, 0, "Shout", 1),
+            sent("a.rs", 2, 1, "Message", 2),
+            sent("a.rs", 2, 1, "Shout", 2),
+            sent("a.rs", 9, 0, "Message", 3),
+            sent("b.rs", 14, 0, "Message", 4),
+            sent("b.rs", 17, 0, "__retrieval_Route", 1),
+        ]
+    );
+    assert_eq!(
+        registry.index(&PathBuf::from("a.rs"), 2, 1, "Shout"),
+        Some(2)
+    );
+    assert_eq!(registry.quantity(&PathBuf::from("b.rs"), 5), None);
+
+    // A trait in the same module is preferred, otherwise the id must be unique or the path must lead to one.
+    let files = [
+        ("a.rs", "#[retrieve] trait Message {}"),
+        (
+            "b.rs",
+            "#[retrieve] trait Message {} #[send] impl Message {}",
+        ),
+        ("c.rs", "#[send] impl Message {}"),
+    ];
+    let registry = Registry::scan(&sources(&files[..2]), &Cfgs::default()).unwrap();
+    assert_eq!(registry.sent, [sent("b.rs", 1, 0, "Message", 1)]);
+    assert!(matches!(
+        Registry::scan(&sources(&files), &Cfgs::default()),
+        Err(Error::Ambiguous { line: 1, .. })
+    ));
+
+    // A send finds its trait by id, and a send that can't be parsed is an error.
+    let registry = Registry::scan(
+        &sources(&[(
+            "a.rs",
+            r#"
+mod chat {
+    #[retrieve(capacity = 10, id = "chat")]
+    pub trait Message {}
+}
+
+#[retrieve]
+trait Message {}
+
+#[send]
+impl Message {}
+
+#[send(id = "chat", also = [Message])]
+impl chat::Message {}
+"#,
+        )]),
+        &Cfgs::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        registry.sent,
+        [
+            sent("a.rs", 10, 0, "Message", 1),
+            sent("a.rs", 13, 0, "Message", 1),
+            sent("a.rs", 13, 0, "Message", 2),
+        ]
+    );
+    assert_eq!(
+        registry
+            .retrieved
+            .iter()
+            .map(|retrieved| retrieved.quantity)
+            .collect::<Vec<_>>(),
+        [1, 2]
+    );
+    assert!(matches!(
+        Registry::scan(
+            &sources(&[(
+                "a.rs",
+                "#[retrieve] trait Message {} #[send(id = chat)] impl Message {}"
+            )]),
+            &Cfgs::default(),
+        ),
+        Err(Error::Parse(..))
+    ));
+
+    // Traits with the same id are told apart by the send's path, from the module of its file.
+    let files = [
+        (
+            "src/lib.rs",
+            r#"
+mod chat {
+    #[retrieve]
+    pub trait Message {}
+
+    #[send]
+    impl Message {}
+}
+
+#[retrieve]
+trait Message {}
+
+#[send]
+impl Message {}
+
+#[send]
+impl chat::Message {}
+
+mod files;
+"#,
+        ),
+        (
+            "src/files.rs",
+            r#"
+#[send]
+impl crate::Message {}
+
+#[send]
+impl super::chat::Message {}
+"#,
+        ),
+    ];
+    let registry = Registry::scan(&sources(&files), &Cfgs::default()).unwrap();
+    assert_eq!(
+        registry.sent,
+        [
+            sent("src/lib.rs", 6, 0, "Message", 1),
+            sent("src/lib.rs", 13, 0, "Message", 1),
+            sent("src/lib.rs", 16, 0, "Message", 2),
+            sent("src/files.rs", 2, 0, "Message", 2),
+            sent("src/files.rs", 5, 0, "Message", 3),
+        ]
+    );
+    assert!(matches!(
+        Registry::scan(
+            &sources(&[files[0], ("src/files.rs", "#[send] impl Message {}")]),
+            &Cfgs::default(),
+        ),
+        Err(Error::Ambiguous { line: 1, .. })
+    ));
+
+    // Evaluated cfgs decide whether a send exists, and unknown ones go last.
+    let cfgs = Cfgs::default()
+        .feature("loud-voice")
+        .option("target_os", Some("linux"));
+    let registry = Registry::scan(
+        &sources(&[
+            (
+                "src/lib.rs",
+                r#"
+#[retrieve]
+trait Message {}
+
+#[send]
+impl Message {}
+
+#[cfg(test)]
+#[send]
+impl Message {}
+
+#[cfg(all(feature = "loud-voice", not(target_os = "windows")))]
+#[send]
+impl Message {}
+
+#[cfg_attr(any(), send)]
+impl Message {}
+
+#[cfg(feature = "quiet")]
+mod quiet {
+    #[send]
+    impl Message {}
+}
+
+#[cfg_attr(target_os = "linux", send)]
+impl Message {}
+
+#[cfg(test)]
+mod tests;
+"#,
+            ),
+            ("src/tests.rs", "#[send] impl Message {}"),
+        ]),
+        &cfgs,
+    )
+    .unwrap();
+    assert_eq!(
+        registry.sent,
+        [
+            sent("src/lib.rs", 5, 0, "Message", 1),
+            sent("src/lib.rs", 13, 0, "Message", 2),
+            sent("src/lib.rs", 25, 0, "Message", 3),
+            sent("src/lib.rs", 9, 0, "Message", 4),
+            sent("src/tests.rs", 1, 0, "Message", 5),
+        ]
+    );
+    assert_eq!(registry.retrieved[0].quantity, 5);
+
+    // Sends behind different unknown cfgs can't all go last.
+    assert!(matches!(
+        Registry::scan(
+            &sources(&[(
+                "a.rs",
+                "#[retrieve] trait Message {} #[cfg(test)] #[send] impl Message {} #[cfg(doc)] #[send] impl Message {}",
+            )]),
+            &cfgs,
+        ),
+        Err(Error::Conditions { .. })
+    ));
+}
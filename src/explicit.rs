@@ -0,0 +1,27 @@
+//! The implementation of explicit mode, where every implementation is sent with its index.\
+//! Uses the same switches as replaceable implementations, but as the index is known, nothing needs to be counted.
+
+use crate::switches;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Path;
+
+/// The prefix of the switches, which are placed in the internal module.
+const PREFIX: &str = "Explicit";
+
+/// Generates the switches, which must be placed in the internal module.
+pub(crate) fn setup(quantity: u32) -> TokenStream {
+    switches::setup(PREFIX, quantity)
+}
+
+/// Marks the index as final, and unmarks the index before it.\
+/// Two implementations with the same index conflict, and a missing index leaves its NEXT unimplemented.
+pub(crate) fn implementation(internal_path: &Path, index: u16) -> TokenStream {
+    switches::implementation(
+        PREFIX,
+        &quote! {#internal_path},
+        internal_path,
+        (index.checked_sub(1).map(u32::from), index.into()),
+        index,
+    )
+}
@@ -0,0 +1,118 @@
+//! The implementation of the `for_each` macro.
+
+use crate::iterate::{IterateAttribute, Order};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Expr, ExprClosure, Ident, Pat, Path, Token,
+    parse::{Parse, Parser},
+    spanned::Spanned,
+};
+
+/// The retrieval trait, then any options, then the closure, separated by commas.
+struct ForEach {
+    module_path: Path,
+    options: IterateAttribute,
+    ident: Ident,
+    body: Expr,
+}
+
+impl Parse for ForEach {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let module_path = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let mut options = IterateAttribute::default();
+        while !input.peek(Token![|]) && !input.peek(Token![move]) {
+            options.parse_option(input)?;
+            input.parse::<Token![,]>()?;
+        }
+        if let Some(trait_path) = &options.trait_path {
+            return Err(syn::Error::new(
+                trait_path.span(),
+                "The retrieval trait is already given first.",
+            ));
+        }
+
+        let closure: ExprClosure = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+
+        let mut inputs = closure.inputs.iter();
+        let (Some(Pat::Ident(generic)), None) = (inputs.next(), inputs.next()) else {
+            return Err(syn::Error::new(
+                closure.inputs.span(),
+                "Expected the closure to take a single ident, which becomes each implementation.",
+            ));
+        };
+
+        Ok(ForEach {
+            module_path,
+            options,
+            ident: generic.ident.clone(),
+            body: *closure.body,
+        })
+    }
+}
+
+/// Copies the body once for every index up to the capacity, as closures can't be generic.\
+/// Each copy has the ident aliased to a different implementation, and is skipped if the index is past the quantity.
+pub(crate) fn for_each_internal(input: TokenStream) -> syn::Result<TokenStream> {
+    let ForEach {
+        module_path,
+        options: IterateAttribute {
+            capacity, order, ..
+        },
+        ident,
+        body,
+    } = syn::parse2(input.clone())?;
+
+    let Some(capacity) = capacity else {
+        // Ask the retrieval trait for its capacity, which expands this again with it.
+        let rest = (|input: syn::parse::ParseStream| {
+            input.parse::<Path>()?;
+            input.parse::<Token![,]>()?;
+            input.parse::<TokenStream>()
+        })
+        .parse2(input)?;
+        let for_each = Ident::new("for_each", proc_macro2::Span::call_site());
+        return Ok(quote! {
+            #module_path::__internal::capacity! { for_each #for_each (#module_path) #rest }
+        });
+    };
+    let check = crate::iterate::quantity_check(&module_path, capacity)?;
+    let capacity = u16::try_from(capacity).map_err(|_| {
+        syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "The capacity must fit in a u16.",
+        )
+    })?;
+
+    let mut indices: Vec<u16> = (1..=capacity).collect();
+    if order == Order::Reverse {
+        indices.reverse();
+    }
+
+    let steps = indices.into_iter().map(|index| {
+        quote! {
+            if #index <= #module_path::QUANTITY {
+                // Indices past the quantity are never used, but they still need to be an implementation.
+                #[allow(dead_code)]
+                type #ident = #module_path::__internal::Container<{
+                    if #index <= #module_path::QUANTITY {
+                        #index
+                    } else {
+                        #module_path::QUANTITY
+                    }
+                }>;
+                #body
+            }
+        }
+    });
+
+    Ok(quote! {{
+        #check
+        #[allow(unused_imports)]
+        use #module_path::Trait as _;
+        #(#steps)*
+    }})
+}
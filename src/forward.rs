@@ -0,0 +1,46 @@
+//! Forwarding a function's generics and inputs on to another function.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{FnArg, Generics, Ident, Signature};
+
+/// The generic arguments that pass a function's generics on unchanged.\
+/// Lifetimes are left to be inferred, as they may be late bound.
+pub(crate) fn generic_arguments(generics: &Generics) -> impl Iterator<Item = &Ident> {
+    generics.params.iter().filter_map(|param| match param {
+        syn::GenericParam::Lifetime(_) => None,
+        syn::GenericParam::Type(param) => Some(&param.ident),
+        syn::GenericParam::Const(param) => Some(&param.ident),
+    })
+}
+
+/// Gives every input a fresh ident, so that any pattern can be forwarded. The receiver is left alone.
+fn rename_inputs(sig: &mut Signature) -> Vec<Ident> {
+    sig.inputs
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(index, input)| {
+            let FnArg::Typed(input) = input else {
+                return None;
+            };
+            let ident = Ident::new(&format!("input_{index}"), Span::mixed_site());
+            *input.pat = syn::parse_quote! {#ident};
+            Some(ident)
+        })
+        .collect()
+}
+
+/// Renames the signature's inputs, and calls the callee with them and the signature's generics.\
+/// The call is awaited if the signature is async, or put in an unsafe block if it is unsafe.
+pub(crate) fn forwarding_call(sig: &mut Signature, callee: &TokenStream) -> TokenStream {
+    let arguments: Vec<&Ident> = generic_arguments(&sig.generics).collect();
+    let arguments = (!arguments.is_empty()).then(|| quote! {::<#(#arguments),*>});
+    let forwarded = rename_inputs(sig);
+
+    let call = quote! {#callee #arguments(#(#forwarded),*)};
+    match (&sig.asyncness, &sig.unsafety) {
+        (Some(_), _) => quote! {#call.await},
+        (None, Some(_)) => quote! {unsafe { #call }},
+        (None, None) => call,
+    }
+}
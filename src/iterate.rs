@@ -0,0 +1,1024 @@
+//! The implementation of the iterate macro.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
+use std::hash::{Hash, Hasher};
+use syn::{
+    FnArg, GenericParam, Generics, Ident, ItemFn, LitInt, Pat, Path, ReturnType, Signature, Token,
+    TraitBound, Type, TypeParamBound, Visibility, WherePredicate, parse::Parse,
+    punctuated::Punctuated, spanned::Spanned,
+};
+
+/// The order in which implementations are visited.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Order {
+    /// From the last implementation sent to the first.
+    #[default]
+    Reverse,
+    /// From the first implementation sent to the last.
+    Forward,
+}
+
+/// Allows the capacity, the order, and the retrieval trait, separated by commas, in any order.\
+/// Like [`RetrieveAttribute`](crate::RetrieveAttribute), the capacity may be a lone number, or have any ident= before it.\
+/// Without a capacity, the retrieval trait's own is used.
+#[derive(Default)]
+pub(crate) struct IterateAttribute {
+    pub(crate) capacity: Option<u32>,
+    pub(crate) order: Order,
+    /// Which bound is the retrieval trait, for when it can't be told apart from the others.
+    pub(crate) trait_path: Option<Path>,
+}
+
+impl IterateAttribute {
+    /// Parses a single option, without the comma.
+    pub(crate) fn parse_option(&mut self, input: syn::parse::ParseStream) -> syn::Result<()> {
+        if input.parse::<Option<Token![trait]>>()?.is_some() {
+            input.parse::<Token![=]>()?;
+            self.trait_path = Some(input.parse()?);
+            return Ok(());
+        }
+
+        match input.parse::<Option<Ident>>()? {
+            Some(ident) if ident == "order" => {
+                input.parse::<Token![=]>()?;
+                let order = input.parse::<Ident>()?;
+                self.order = if order == "forward" {
+                    Order::Forward
+                } else if order == "reverse" {
+                    Order::Reverse
+                } else {
+                    return Err(syn::Error::new(
+                        order.span(),
+                        "Expected the order to be forward or reverse.",
+                    ));
+                };
+            }
+            Some(_) => {
+                input.parse::<Token![=]>()?;
+                self.capacity = Some(input.parse::<LitInt>()?.base10_parse()?);
+            }
+            None => self.capacity = Some(input.parse::<LitInt>()?.base10_parse()?),
+        }
+        Ok(())
+    }
+}
+
+impl Parse for IterateAttribute {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut attribute = IterateAttribute::default();
+
+        while !input.is_empty() {
+            attribute.parse_option(input)?;
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(attribute)
+    }
+}
+
+/// What an iterate function does with its return value.
+enum Mode {
+    /// Nothing is returned.
+    Unit,
+    /// The first input (other than self) is an accumulator. It is passed through every implementation, and then returned.
+    Fold(Ident),
+    /// Returns a `ControlFlow`, stopping at the first Break.
+    /// Contains the Break's type.
+    Search(Box<Type>),
+}
+
+impl Mode {
+    /// Expects the inputs to have already been [renamed](rename).
+    fn new(sig: &Signature) -> syn::Result<Self> {
+        let ty = match &sig.output {
+            ReturnType::Default => return Ok(Mode::Unit),
+            ReturnType::Type(_, ty) => &**ty,
+        };
+        if let Type::Tuple(tuple) = ty
+            && tuple.elems.is_empty()
+        {
+            return Ok(Mode::Unit);
+        }
+        if let Some(break_ty) = control_flow_break(ty)? {
+            return Ok(Mode::Search(Box::new(break_ty)));
+        }
+
+        let Some(FnArg::Typed(accumulator)) = sig
+            .inputs
+            .iter()
+            .find(|input| matches!(input, FnArg::Typed(_)))
+        else {
+            return Err(syn::Error::new(
+                sig.output.span(),
+                "A function that returns a value must take the accumulator as its first input.",
+            ));
+        };
+        let Pat::Ident(accumulator) = &*accumulator.pat else {
+            unreachable!("Inputs are renamed to idents before this.");
+        };
+
+        Ok(Mode::Fold(accumulator.ident.clone()))
+    }
+
+    /// The external function's block contents, given the call to the first internal function.
+    fn external_block(&self, start: &TokenStream) -> TokenStream {
+        match self {
+            Mode::Unit => quote! {
+                #start;
+            },
+            Mode::Fold(_) => quote! {
+                #start
+            },
+            Mode::Search(_) => quote! {
+                match #start {
+                    ::core::ops::ControlFlow::Break(value) => ::core::option::Option::Some(value),
+                    ::core::ops::ControlFlow::Continue(()) => ::core::option::Option::None,
+                }
+            },
+        }
+    }
+
+    /// Wraps an internal function's block so that it stops at the end of the chain, and otherwise calls the next internal function.\
+    /// In forward order, the next internal function is called first, so that the implementation at the end of the chain goes first.
+    fn internal_block(
+        &self,
+        block: &TokenStream,
+        generic_ident: &Ident,
+        next: &TokenStream,
+        order: Order,
+    ) -> TokenStream {
+        match (self, order) {
+            (Mode::Unit, Order::Reverse) => quote! {{
+                if #generic_ident::END {
+                    return;
+                }
+                #block;
+                #next;
+            }},
+            (Mode::Unit, Order::Forward) => quote! {{
+                if #generic_ident::END {
+                    return;
+                }
+                #next;
+                #block;
+            }},
+            (Mode::Fold(accumulator), Order::Reverse) => quote! {{
+                if #generic_ident::END {
+                    return #accumulator;
+                }
+                let #accumulator = #block;
+                #next
+            }},
+            (Mode::Fold(accumulator), Order::Forward) => quote! {{
+                if #generic_ident::END {
+                    return #accumulator;
+                }
+                let #accumulator = #next;
+                #block
+            }},
+            (Mode::Search(_), Order::Reverse) => quote! {{
+                if #generic_ident::END {
+                    return ::core::ops::ControlFlow::Continue(());
+                }
+                match #block {
+                    ::core::ops::ControlFlow::Break(value) => {
+                        return ::core::ops::ControlFlow::Break(value);
+                    }
+                    ::core::ops::ControlFlow::Continue(()) => {}
+                }
+                #next
+            }},
+            (Mode::Search(_), Order::Forward) => quote! {{
+                if #generic_ident::END {
+                    return ::core::ops::ControlFlow::Continue(());
+                }
+                match #next {
+                    ::core::ops::ControlFlow::Break(value) => {
+                        return ::core::ops::ControlFlow::Break(value);
+                    }
+                    ::core::ops::ControlFlow::Continue(()) => {}
+                }
+                #block
+            }},
+        }
+    }
+
+    /// The external function's block contents, when every implementation is called individually.\
+    /// Each call is paired with the condition under which it should happen.
+    fn checked_block(&self, calls: &[(TokenStream, TokenStream)]) -> TokenStream {
+        let steps = calls.iter().map(|(condition, call)| match self {
+            Mode::Unit => quote! {
+                if #condition {
+                    #call;
+                }
+            },
+            Mode::Fold(accumulator) => quote! {
+                let #accumulator = if #condition {
+                    #call
+                } else {
+                    #accumulator
+                };
+            },
+            Mode::Search(_) => quote! {
+                if #condition {
+                    match #call {
+                        ::core::ops::ControlFlow::Break(value) => {
+                            return ::core::option::Option::Some(value);
+                        }
+                        ::core::ops::ControlFlow::Continue(()) => {}
+                    }
+                }
+            },
+        });
+        let end = match self {
+            Mode::Unit => quote! {},
+            Mode::Fold(accumulator) => quote! {#accumulator},
+            Mode::Search(_) => quote! {::core::option::Option::None},
+        };
+
+        quote! {
+            #(#steps)*
+            #end
+        }
+    }
+}
+
+/// If the type is a `ControlFlow`, then this returns its Break type.
+fn control_flow_break(ty: &Type) -> syn::Result<Option<Type>> {
+    let Type::Path(path) = ty else {
+        return Ok(None);
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return Ok(None);
+    };
+    if segment.ident != "ControlFlow" {
+        return Ok(None);
+    }
+
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return Err(syn::Error::new(
+            segment.span(),
+            "Expected ControlFlow to have a Break type.",
+        ));
+    };
+    let mut arguments = arguments.args.iter();
+    let Some(syn::GenericArgument::Type(break_ty)) = arguments.next() else {
+        return Err(syn::Error::new(
+            segment.span(),
+            "Expected ControlFlow to have a Break type.",
+        ));
+    };
+    if let Some(continue_ty) = arguments.next() {
+        let syn::GenericArgument::Type(Type::Tuple(tuple)) = continue_ty else {
+            return Err(syn::Error::new(
+                continue_ty.span(),
+                "The Continue type must be ().",
+            ));
+        };
+        if !tuple.elems.is_empty() {
+            return Err(syn::Error::new(
+                continue_ty.span(),
+                "The Continue type must be ().",
+            ));
+        }
+    }
+
+    Ok(Some(break_ty.clone()))
+}
+
+/// Traits from the standard library that are commonly used as bounds.\
+/// These are never retrieval traits, so they are skipped when looking for one.
+const STANDARD_TRAITS: &[&str] = &[
+    "Any",
+    "Clone",
+    "Copy",
+    "Debug",
+    "Default",
+    "Display",
+    "Eq",
+    "Hash",
+    "Ord",
+    "PartialEq",
+    "PartialOrd",
+    "Send",
+    "Sized",
+    "Sync",
+    "Unpin",
+];
+
+/// Could this bound be a retrieval trait?\
+/// Retrieval traits are modules, so they never have generic arguments.
+fn is_retrieval_bound(bound: &TraitBound) -> bool {
+    if !matches!(bound.modifier, syn::TraitBoundModifier::None) || bound.lifetimes.is_some() {
+        return false;
+    }
+    let Some(segment) = bound.path.segments.last() else {
+        return false;
+    };
+
+    segment.arguments.is_empty() && !STANDARD_TRAITS.contains(&&*segment.ident.to_string())
+}
+
+/// The path as written, for errors.
+fn path_name(path: &Path) -> String {
+    quote! {#path}.to_string().replace(' ', "")
+}
+
+/// Does anything other than the retrieval trait need to be true for this bound to hold?
+fn is_extra_bound(bound: &TypeParamBound, retrieval_trait: &Path) -> bool {
+    match bound {
+        TypeParamBound::Trait(bound) => {
+            let path = &bound.path;
+            !matches!(bound.modifier, syn::TraitBoundModifier::Maybe(_))
+                && quote! {#path}.to_string() != quote! {#retrieval_trait}.to_string()
+        }
+        _ => true,
+    }
+}
+
+/// The generic that is bound by a retrieval trait.
+struct RetrievalGeneric {
+    /// The retrieval trait's module.
+    module_path: Path,
+    ident: Ident,
+    /// Does the function require anything else of the retrieval generic?\
+    /// If so, then every implementation has to be checked individually.
+    checked: bool,
+}
+
+impl RetrievalGeneric {
+    /// Finds the generic that is bound by a retrieval trait, which is the first type param.\
+    /// The retrieval trait is the only bound that looks like one, or the one given. It may be in the where clause.\
+    /// The bound is changed to point at the actual trait in the module.
+    fn new(generics: &mut Generics, trait_path: Option<&Path>) -> syn::Result<Self> {
+        let generics_span = generics.span();
+        let generic = generics
+            .params
+            .iter_mut()
+            .find_map(|param| match param {
+                GenericParam::Type(param) => Some(param),
+                _ => None,
+            })
+            .ok_or(syn::Error::new(
+                generics_span,
+                "Expected a type param bound by a retrieval trait.",
+            ))?;
+        let ident = generic.ident.clone();
+
+        let predicates = generics
+            .where_clause
+            .iter_mut()
+            .flat_map(|where_clause| where_clause.predicates.iter_mut())
+            .filter_map(|predicate| match predicate {
+                WherePredicate::Type(predicate)
+                    if matches!(&predicate.bounded_ty, Type::Path(path) if path.path.is_ident(&ident)) =>
+                {
+                    Some(predicate.bounds.iter_mut())
+                }
+                _ => None,
+            })
+            .flatten();
+        let mut candidates: Vec<&mut TraitBound> = generic
+            .bounds
+            .iter_mut()
+            .chain(predicates)
+            .filter_map(|bound| match bound {
+                TypeParamBound::Trait(bound) if is_retrieval_bound(bound) => Some(bound),
+                _ => None,
+            })
+            .collect();
+
+        let trait_bound = if let Some(trait_path) = trait_path {
+            let expected = path_name(trait_path);
+            candidates
+                .into_iter()
+                .find(|bound| path_name(&bound.path) == expected)
+                .ok_or(syn::Error::new(
+                    trait_path.span(),
+                    format!("`{ident}` is not bound by `{expected}`."),
+                ))?
+        } else {
+            if candidates.len() > 1 {
+                let names: Vec<String> = candidates
+                    .iter()
+                    .map(|bound| format!("`{}`", path_name(&bound.path)))
+                    .collect();
+                return Err(syn::Error::new(
+                    candidates[1].span(),
+                    format!(
+                        "Any of {} could be the retrieval trait bounding `{ident}`. Choose one with #[iterate(trait = ...)].",
+                        names.join(", "),
+                    ),
+                ));
+            }
+            candidates.pop().ok_or(syn::Error::new(
+                ident.span(),
+                "Could not find a retrieval trait bounding this generic.",
+            ))?
+        };
+        let module_path = trait_bound.path.clone();
+        trait_bound.path.segments.push(syn::parse2(quote! {Trait})?);
+        let retrieval_trait = trait_bound.path.clone();
+
+        let generic = generics
+            .type_params()
+            .next()
+            .expect("The generic was found above.");
+        let extra_bounds = generic
+            .bounds
+            .iter()
+            .any(|bound| is_extra_bound(bound, &retrieval_trait));
+        let extra_predicates = generics.where_clause.iter().any(|where_clause| {
+            where_clause.predicates.iter().any(|predicate| match predicate {
+                WherePredicate::Type(predicate)
+                    if matches!(&predicate.bounded_ty, Type::Path(path) if path.path.is_ident(&ident)) =>
+                {
+                    predicate
+                        .bounds
+                        .iter()
+                        .any(|bound| is_extra_bound(bound, &retrieval_trait))
+                }
+                predicate => mentions(quote! {#predicate}, &ident),
+            })
+        });
+
+        Ok(RetrievalGeneric {
+            module_path,
+            ident,
+            checked: extra_bounds || extra_predicates,
+        })
+    }
+}
+
+/// Does the token stream contain the ident anywhere?
+pub(crate) fn mentions(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(token) => token == *ident,
+        proc_macro2::TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// The generics of the external function.
+/// These are the same as the internal functions' generics, but without the retrieval generic or anything that mentions it.
+fn external_generics(generics: &Generics, generic_ident: &Ident) -> Generics {
+    let mut generics = generics.clone();
+    generics.params = generics
+        .params
+        .into_iter()
+        .filter(|param| !matches!(param, syn::GenericParam::Type(param) if param.ident == *generic_ident))
+        .collect();
+    if let Some(where_clause) = &mut generics.where_clause {
+        where_clause.predicates = where_clause
+            .predicates
+            .clone()
+            .into_iter()
+            .filter(|predicate| !mentions(quote! {#predicate}, generic_ident))
+            .collect();
+    }
+    generics
+}
+
+/// The generic arguments used to call an internal function, with the retrieval generic replaced.\
+/// Lifetimes are left to be inferred, as they may be late bound.
+fn generic_arguments(
+    generics: &Generics,
+    generic_ident: &Ident,
+    retrieval: &TokenStream,
+) -> Vec<TokenStream> {
+    crate::forward::generic_arguments(generics)
+        .map(|ident| {
+            if ident == generic_ident {
+                retrieval.clone()
+            } else {
+                quote! {#ident}
+            }
+        })
+        .collect()
+}
+
+/// A fresh ident for an input, pointing at its pattern so that errors about it make sense.
+fn input_ident(index: usize, pat: &Pat) -> Ident {
+    Ident::new(
+        &format!("input_{index}"),
+        Span::mixed_site().located_at(pat.span()),
+    )
+}
+
+/// Gives every input that isn't an ident a fresh ident, so that any pattern can be forwarded.\
+/// Idents lose their mut, as they are only passed on. Returns the expressions to forward.
+fn rename(sig: &mut Signature) -> Vec<TokenStream> {
+    sig.inputs
+        .iter_mut()
+        .enumerate()
+        .map(|(index, input)| {
+            let input = match input {
+                // The receiver's own self token keeps its hygiene, in case this came through a macro.
+                FnArg::Receiver(receiver) => return receiver.self_token.to_token_stream(),
+                FnArg::Typed(input) => input,
+            };
+
+            if let Pat::Ident(pat) = &mut *input.pat
+                && pat.by_ref.is_none()
+                && pat.subpat.is_none()
+            {
+                pat.mutability = None;
+                let ident = &pat.ident;
+                return quote! {#ident};
+            }
+
+            let ident = input_ident(index, &input.pat);
+            *input.pat = syn::parse_quote! {#ident};
+            quote! {#ident}
+        })
+        .collect()
+}
+
+/// The expression that builds the value a pattern destructured, out of the pattern's bindings.\
+/// None if the pattern doesn't bind the whole value by value, such as with `..`, `_`, `ref`, or a literal.
+fn rebuild(pat: &Pat) -> Option<TokenStream> {
+    let all = |pats: &mut dyn Iterator<Item = &Pat>| pats.map(rebuild).collect::<Option<Vec<_>>>();
+    match pat {
+        Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => {
+            let ident = &pat.ident;
+            Some(quote! {#ident})
+        }
+        Pat::Paren(pat) => rebuild(&pat.pat),
+        Pat::Tuple(pat) => {
+            let elems = all(&mut pat.elems.iter())?;
+            Some(quote! {(#(#elems,)*)})
+        }
+        Pat::Slice(pat) => {
+            let elems = all(&mut pat.elems.iter())?;
+            Some(quote! {[#(#elems),*]})
+        }
+        Pat::TupleStruct(pat) if pat.qself.is_none() => {
+            let path = &pat.path;
+            let elems = all(&mut pat.elems.iter())?;
+            Some(quote! {#path(#(#elems),*)})
+        }
+        Pat::Struct(pat) if pat.qself.is_none() && pat.rest.is_none() => {
+            let path = &pat.path;
+            let fields = pat
+                .fields
+                .iter()
+                .map(|field| {
+                    let value = rebuild(&field.pat)?;
+                    let member = &field.member;
+                    Some(match field.colon_token {
+                        Some(_) => quote! {#member: #value},
+                        None => value,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(quote! {#path { #(#fields),* }})
+        }
+        _ => None,
+    }
+}
+
+/// Makes the inputs ready to be passed on to the next implementation, after the block has used them.\
+/// Idents are passed on as they are, and destructured inputs are rebuilt from their bindings, so that neither has to be Copy.
+/// Any changes the block makes to them are passed on as well.\
+/// Other patterns, and the accumulator if the function has one, are given a fresh ident that the pattern is applied to in the block.\
+/// Returns the expressions to forward, and the let statements that apply the patterns to the fresh idents.
+fn rebind(sig: &mut Signature, accumulator: bool) -> (Vec<TokenStream>, TokenStream) {
+    let mut forwarded = Vec::with_capacity(sig.inputs.len());
+    let mut bindings = TokenStream::new();
+    let mut accumulator = accumulator;
+
+    for (index, input) in sig.inputs.iter_mut().enumerate() {
+        let input = match input {
+            FnArg::Receiver(receiver) => {
+                forwarded.push(receiver.self_token.to_token_stream());
+                continue;
+            }
+            FnArg::Typed(input) => input,
+        };
+        // The accumulator is replaced by the block's value, so it must be an ident that can be assigned to.
+        let is_accumulator = std::mem::take(&mut accumulator);
+
+        if let Pat::Ident(pat) = &*input.pat
+            && pat.by_ref.is_none()
+            && pat.subpat.is_none()
+        {
+            let ident = &pat.ident;
+            forwarded.push(quote! {#ident});
+            continue;
+        }
+        if !is_accumulator && let Some(rebuilt) = rebuild(&input.pat) {
+            forwarded.push(rebuilt);
+            continue;
+        }
+
+        let ident = input_ident(index, &input.pat);
+        let pat = std::mem::replace(&mut *input.pat, syn::parse_quote! {#ident});
+        let mut ty = (*input.ty).clone();
+        infer_impl_trait(&mut ty);
+        // Annotating the type means references are reborrowed instead of moved.
+        // Inputs were never reported as unused before they were rebound, so that stays the same.
+        bindings.extend(quote! {
+            #[allow(unused_variables)]
+            let #pat: #ty = #ident;
+        });
+        forwarded.push(quote! {#ident});
+    }
+
+    (forwarded, bindings)
+}
+
+/// Impl trait can't be used in a let statement, so it is replaced with an inferred type.\
+/// References are kept, so that they are still reborrowed.
+fn infer_impl_trait(ty: &mut Type) {
+    match ty {
+        Type::ImplTrait(_) => {
+            *ty = Type::Infer(syn::TypeInfer {
+                underscore_token: <syn::Token![_]>::default(),
+            });
+        }
+        Type::Reference(reference) => infer_impl_trait(&mut reference.elem),
+        Type::Ptr(pointer) => infer_impl_trait(&mut pointer.elem),
+        Type::Slice(slice) => infer_impl_trait(&mut slice.elem),
+        Type::Array(array) => infer_impl_trait(&mut array.elem),
+        Type::Paren(paren) => infer_impl_trait(&mut paren.elem),
+        Type::Group(group) => infer_impl_trait(&mut group.elem),
+        Type::Tuple(tuple) => tuple.elems.iter_mut().for_each(infer_impl_trait),
+        ty => {
+            if mentions(quote! {#ty}, &Ident::new("impl", Span::call_site())) {
+                *ty = Type::Infer(syn::TypeInfer {
+                    underscore_token: <syn::Token![_]>::default(),
+                });
+            }
+        }
+    }
+}
+
+/// Removes the input marked with `#[position]`, if there is one.\
+/// Returns its pattern and type.
+fn take_position(sig: &mut Signature) -> syn::Result<Option<(Pat, Type)>> {
+    let is_position = |attribute: &syn::Attribute| attribute.path().is_ident("position");
+
+    let mut position = None;
+    let mut inputs = Punctuated::new();
+    for input in std::mem::take(&mut sig.inputs) {
+        match input {
+            FnArg::Typed(mut input) if input.attrs.iter().any(is_position) => {
+                if position.is_some() {
+                    return Err(syn::Error::new(
+                        input.span(),
+                        "Only one input can be marked with #[position].",
+                    ));
+                }
+                input.attrs.retain(|attribute| !is_position(attribute));
+                position = Some((*input.pat, *input.ty));
+            }
+            FnArg::Receiver(receiver) if receiver.attrs.iter().any(is_position) => {
+                return Err(syn::Error::new(
+                    receiver.span(),
+                    "Self cannot be the position.",
+                ));
+            }
+            input => inputs.push(input),
+        }
+    }
+    sig.inputs = inputs;
+
+    Ok(position)
+}
+
+/// Where the internal functions are placed.
+enum Placement {
+    /// Inside the external function's block, which is valid anywhere.
+    Nested,
+    /// Next to the external function, called through Self.\
+    /// Required when the function uses Self, which nested functions cannot.
+    Associated,
+}
+
+/// Everything shared between the internal functions.
+struct Expansion<'a> {
+    capacity: u32,
+    order: Order,
+    retrieval: &'a RetrievalGeneric,
+    mode: &'a Mode,
+    placement: Placement,
+    /// The expressions forwarded to each internal function.
+    forwarded: &'a [TokenStream],
+    external_ident: &'a Ident,
+}
+
+impl Expansion<'_> {
+    /// Calls an internal function, with the given generic arguments and inputs.
+    fn call(
+        &self,
+        ident: &Ident,
+        arguments: &[TokenStream],
+        forwarded: &[TokenStream],
+    ) -> TokenStream {
+        match self.placement {
+            Placement::Nested => quote! {#ident::<#(#arguments),*>(#(#forwarded),*)},
+            Placement::Associated => quote! {Self::#ident::<#(#arguments),*>(#(#forwarded),*)},
+        }
+    }
+
+    /// Walks the chain of implementations using recursive internal functions.\
+    /// Returns the external function's block contents, and the internal functions.
+    fn chained(&self, internal: &ItemFn) -> syn::Result<(TokenStream, TokenStream)> {
+        // Work out how many functions we will need to reach the target recursion limit.
+        // Even with no capacity, one is needed to reach the end of the chain.
+        let functions_needed = self.capacity.div_ceil(128).max(1);
+        let RetrievalGeneric {
+            module_path,
+            ident: generic_ident,
+            ..
+        } = self.retrieval;
+        let external_ident = self.external_ident;
+
+        // The first internal function's ident.
+        let internal_start_ident =
+            Ident::new(&format!("__internal_0_{external_ident}"), Span::call_site());
+
+        let start_arguments = generic_arguments(
+            &internal.sig.generics,
+            generic_ident,
+            &quote! {#module_path::__internal::Container<{#module_path::QUANTITY}>},
+        );
+        let next_arguments = generic_arguments(
+            &internal.sig.generics,
+            generic_ident,
+            &quote! {#generic_ident::NEXT},
+        );
+        let start = self.call(&internal_start_ident, &start_arguments, self.forwarded);
+        let external_block = self.mode.external_block(&start);
+
+        let mut internals = TokenStream::new();
+        for index in 0..functions_needed {
+            let next_index = if index == functions_needed - 1 {
+                0
+            } else {
+                index + 1
+            };
+            let mut internal = internal.clone();
+            let (forwarded, bindings) =
+                rebind(&mut internal.sig, matches!(self.mode, Mode::Fold(_)));
+            let block = &internal.block;
+            let block = quote! {{
+                #bindings
+                #block
+            }};
+
+            internal.sig.ident = Ident::new(
+                &format!("__internal_{}_{}", index, &external_ident),
+                Span::call_site(),
+            );
+
+            let internal_next_ident = Ident::new(
+                &format!("__internal_{next_index}_{external_ident}"),
+                Span::call_site(),
+            );
+            let next = self.call(&internal_next_ident, &next_arguments, &forwarded);
+
+            *internal.block =
+                syn::parse2(
+                    self.mode
+                        .internal_block(&block, generic_ident, &next, self.order),
+                )?;
+
+            internals.extend(quote! {#internal});
+        }
+
+        Ok((external_block, internals))
+    }
+
+    /// Calls a single internal function on every implementation individually, so that any extra bounds are checked against each one.\
+    /// Every index up to the capacity gets a call, as we do not know the quantity in advance.\
+    /// Returns the external function's block contents, and the internal function.
+    fn checked(&self, mut internal: ItemFn) -> syn::Result<(TokenStream, TokenStream)> {
+        let RetrievalGeneric {
+            module_path,
+            ident: generic_ident,
+            ..
+        } = self.retrieval;
+        let external_ident = self.external_ident;
+        let capacity = u16::try_from(self.capacity).map_err(|_| {
+            syn::Error::new(
+                Span::call_site(),
+                "The capacity must fit in a u16 when the retrieval generic has extra bounds.",
+            )
+        })?;
+
+        internal.sig.ident = Ident::new(&format!("__internal_{external_ident}"), Span::call_site());
+        let internal_ident = &internal.sig.ident;
+
+        let mut indices: Vec<u16> = (1..=capacity).collect();
+        if self.order == Order::Reverse {
+            indices.reverse();
+        }
+        let calls: Vec<(TokenStream, TokenStream)> = indices
+            .into_iter()
+            .map(|index| {
+                // Indices past the quantity are never called, but they still need to be an implementation.
+                let arguments = generic_arguments(
+                    &internal.sig.generics,
+                    generic_ident,
+                    &quote! {
+                        #module_path::__internal::Container<{
+                            if #index <= #module_path::QUANTITY {
+                                #index
+                            } else {
+                                #module_path::QUANTITY
+                            }
+                        }>
+                    },
+                );
+                (
+                    quote! {#index <= #module_path::QUANTITY},
+                    self.call(internal_ident, &arguments, self.forwarded),
+                )
+            })
+            .collect();
+
+        Ok((self.mode.checked_block(&calls), quote! {#internal}))
+    }
+}
+
+/// With extra bounds, every implementation is called separately with the same inputs, so each one taken by value must be Copy.\
+/// Fails to compile with an explanation if one isn't. References are reborrowed, and the accumulator is replaced by each call's value.
+fn copy_checks(sig: &Signature, mode: &Mode) -> TokenStream {
+    let checks: Vec<TokenStream> = sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(input) => Some(input),
+            FnArg::Receiver(_) => None,
+        })
+        .skip(usize::from(matches!(mode, Mode::Fold(_))))
+        .filter(|input| !matches!(*input.ty, Type::Reference(_)))
+        .map(|input| {
+            let pat = &input.pat;
+            quote_spanned! {input.ty.span()=>
+                copied(&#pat);
+            }
+        })
+        .collect();
+    if checks.is_empty() {
+        return TokenStream::new();
+    }
+
+    quote! {
+        #[diagnostic::on_unimplemented(
+            message = "`{Self}` is given to every implementation separately, so it must be Copy.",
+            label = "not Copy",
+            note = "The retrieval generic has extra bounds, so each implementation is called on its own. Take the input by reference instead."
+        )]
+        trait Copied {}
+        impl<T: ::core::marker::Copy + ?::core::marker::Sized> Copied for T {}
+        const fn copied<T: Copied + ?::core::marker::Sized>(_: &T) {}
+        #(#checks)*
+    }
+}
+
+/// Generates the macro that gives a retrieval trait's capacity to an iterate function or `for_each` that wasn't given one, by expanding it again with it.\
+/// It is exported under a name unique to the trait, so that it can be reached from other crates, and is then used through the trait's module.
+pub(crate) fn capacity_hop(trait_ident: &Ident, capacity: u32) -> TokenStream {
+    let span = proc_macro::Span::call_site();
+    let mut hasher = std::hash::DefaultHasher::new();
+    (
+        span.file(),
+        span.line(),
+        span.column(),
+        trait_ident.to_string(),
+    )
+        .hash(&mut hasher);
+    let unique = format_ident!("__retrieval_capacity_{trait_ident}_{:x}", hasher.finish());
+
+    quote! {
+        /// Gives this trait's capacity to an iterate function or for_each that wasn't given one.\
+        /// They are expanded again through the name they were called by.
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! #unique {
+            (fn $iterate:ident ($($options:tt)*) $($item:tt)*) => {
+                #[$iterate(capacity = #capacity, $($options)*)]
+                $($item)*
+            };
+            (for_each $for_each:ident ($($module_path:tt)*) $($rest:tt)*) => {
+                $for_each!($($module_path)*, capacity = #capacity, $($rest)*)
+            };
+        }
+        #[doc(hidden)]
+        pub use #unique as capacity;
+    }
+}
+
+/// Fails to compile if the retrieval trait has more implementations than the capacity can reach.\
+/// Otherwise the extra implementations would be skipped, or exceed the recursion limit.
+pub(crate) fn quantity_check(module_path: &Path, capacity: u32) -> syn::Result<TokenStream> {
+    let message = format!(
+        "`{}` has more implementations than the capacity of {capacity}. Raise the capacity, or remove it to use the trait's own.",
+        crate::last_ident(module_path)?,
+    );
+    Ok(quote! {
+        const {
+            ::core::assert!(#module_path::QUANTITY as u32 <= #capacity, #message);
+        }
+    })
+}
+
+pub(crate) fn iterate_internal(
+    input: TokenStream,
+    mut internal: ItemFn,
+) -> syn::Result<TokenStream> {
+    let options = input.clone();
+    let IterateAttribute {
+        capacity,
+        order,
+        trait_path,
+    } = syn::parse2(input)?;
+
+    let Some(capacity) = capacity else {
+        // Ask the retrieval trait for its capacity, which expands this again with it.
+        let module_path =
+            RetrievalGeneric::new(&mut internal.sig.generics.clone(), trait_path.as_ref())?
+                .module_path;
+        let iterate = Ident::new("iterate", Span::call_site());
+        return Ok(quote! {
+            #module_path::__internal::capacity! { fn #iterate (#options) #internal }
+        });
+    };
+
+    let retrieval = RetrievalGeneric::new(&mut internal.sig.generics, trait_path.as_ref())?;
+    let generic_ident = &retrieval.ident;
+    let check = quantity_check(&retrieval.module_path, capacity)?;
+
+    if let Some((pat, ty)) = take_position(&mut internal.sig)? {
+        let module_path = &retrieval.module_path;
+        let index = quote! {<#generic_ident as #module_path::Trait>::INDEX};
+        let position = match order {
+            Order::Reverse => quote! {#module_path::QUANTITY - #index},
+            Order::Forward => quote! {#index - 1},
+        };
+        let block = &internal.block;
+        *internal.block = syn::parse2(quote! {{
+            let #pat: #ty = (#position) as #ty;
+            #block
+        }})?;
+    }
+
+    // Create the external function's signature from the internal's but without the retrieval generic.
+    let mut external_sig = internal.sig.clone();
+    external_sig.generics = external_generics(&internal.sig.generics, generic_ident);
+    let forwarded = rename(&mut external_sig);
+
+    let mode = Mode::new(&external_sig)?;
+    if let Mode::Search(break_ty) = &mode {
+        external_sig.output = syn::parse2(quote! {-> ::core::option::Option<#break_ty>})?;
+    }
+
+    let placement = if internal.sig.receiver().is_some()
+        || mentions(quote! {#internal}, &Ident::new("Self", Span::call_site()))
+    {
+        Placement::Associated
+    } else {
+        Placement::Nested
+    };
+
+    let external_vis = internal.vis.clone();
+    internal.vis = Visibility::Inherited;
+    let expansion = Expansion {
+        capacity,
+        order,
+        retrieval: &retrieval,
+        mode: &mode,
+        placement,
+        forwarded: &forwarded,
+        external_ident: &external_sig.ident,
+    };
+    let (external_block, internals) = if retrieval.checked {
+        let (external_block, internals) = expansion.checked(internal)?;
+        let copies = copy_checks(&external_sig, &mode);
+        (quote! {#copies #external_block}, internals)
+    } else {
+        expansion.chained(&internal)?
+    };
+
+    Ok(match expansion.placement {
+        Placement::Nested => quote! {
+            #external_vis #external_sig {
+                #check
+                #internals
+                #external_block
+            }
+        },
+        Placement::Associated => quote! {
+            #external_vis #external_sig {
+                #check
+                #external_block
+            }
+            #internals
+        },
+    })
+}
@@ -0,0 +1,168 @@
+//! The implementation of Kind, which names each implementation with a plain value.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use std::collections::BTreeMap;
+use syn::{GenericArgument, Ident, ItemTrait, PathArguments, Type};
+
+/// Elided lifetimes in an associated const's type are static, so they are made explicit for use elsewhere.\
+/// Function pointers and trait objects are left alone, as their elided lifetimes mean something else.
+fn static_lifetimes(ty: &mut Type) {
+    match ty {
+        Type::Reference(reference) => {
+            if reference.lifetime.is_none() {
+                reference.lifetime = Some(syn::Lifetime::new("'static", Span::call_site()));
+            }
+            static_lifetimes(&mut reference.elem);
+        }
+        Type::Path(path) => {
+            for segment in &mut path.path.segments {
+                let PathArguments::AngleBracketed(arguments) = &mut segment.arguments else {
+                    continue;
+                };
+                for argument in &mut arguments.args {
+                    match argument {
+                        GenericArgument::Lifetime(lifetime) if lifetime.ident == "_" => {
+                            *lifetime = syn::Lifetime::new("'static", Span::call_site());
+                        }
+                        GenericArgument::Type(ty) => static_lifetimes(ty),
+                        _ => (),
+                    }
+                }
+            }
+        }
+        Type::Slice(slice) => static_lifetimes(&mut slice.elem),
+        Type::Array(array) => static_lifetimes(&mut array.elem),
+        Type::Paren(paren) => static_lifetimes(&mut paren.elem),
+        Type::Group(group) => static_lifetimes(&mut group.elem),
+        Type::Tuple(tuple) => tuple.elems.iter_mut().for_each(static_lifetimes),
+        _ => (),
+    }
+}
+
+/// Fails if two of Kind's methods would have the same name.\
+/// Const accessors are the const's name in lower case, and fn accessors are the fn's own name, so they can clash.
+fn check_accessors(item: &ItemTrait, dispatch: bool) -> syn::Result<()> {
+    let mut taken = BTreeMap::from([
+        ("index".to_string(), "Kind's own index".to_string()),
+        (
+            "from_index".to_string(),
+            "Kind's own from_index".to_string(),
+        ),
+    ]);
+    if dispatch {
+        taken.insert("dispatch".to_string(), "Kind's own dispatch".to_string());
+    }
+
+    let consts = crate::collectable_consts(item)
+        .map(|item| (&item.ident, item.ident.to_string().to_lowercase(), "const"));
+    let fns =
+        crate::collectable_fns(item).map(|(sig, _)| (&sig.ident, sig.ident.to_string(), "fn"));
+    for (ident, accessor, kind) in consts.chain(fns) {
+        if let Some(owner) = taken.get(&accessor) {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "Kind's accessor for {kind} `{ident}` would be called `{accessor}`, which is already {owner}. Rename one of them."
+                ),
+            ));
+        }
+        taken.insert(accessor, format!("the accessor for {kind} `{ident}`"));
+    }
+
+    Ok(())
+}
+
+/// Generates Kind, which relies on the arrays generated by `collect_consts` and `collect_fns` for its accessors.
+pub(crate) fn kind_internal(item: &ItemTrait, dispatch: bool) -> syn::Result<TokenStream> {
+    check_accessors(item, dispatch)?;
+
+    let const_accessors = crate::collectable_consts(item).map(|item| {
+        let ident = &item.ident;
+        let all_ident = Ident::new(&format!("ALL_{ident}"), ident.span());
+        let accessor_ident = Ident::new(&ident.to_string().to_lowercase(), ident.span());
+        let mut ty = item.ty.clone();
+        static_lifetimes(&mut ty);
+        let doc = format!("This kind's {ident}.");
+
+        quote! {
+            #[doc = #doc]
+            pub const fn #accessor_ident(self) -> &'static #ty {
+                &(const { &#all_ident })[self.0 as usize]
+            }
+        }
+    });
+
+    let fn_accessors = crate::collectable_fns(item).map(|(sig, _)| {
+        let ident = &sig.ident;
+        let array_ident = Ident::new(&ident.to_string().to_uppercase(), ident.span());
+        let mut sig = sig.clone();
+        let call =
+            crate::forward::forwarding_call(&mut sig, &quote! {(#array_ident[self.0 as usize])});
+        sig.inputs.insert(0, syn::parse_quote! {self});
+        let doc = format!("Calls this kind's {ident}.");
+
+        quote! {
+            #[doc = #doc]
+            pub #sig {
+                #call
+            }
+        }
+    });
+
+    let dispatch = dispatch.then(|| {
+        quote! {
+            /// Visits this kind's implementation.
+            pub fn dispatch<V: Visitor>(self, visitor: V) -> V::Output {
+                match dispatch(self.0, visitor) {
+                    ::core::option::Option::Some(output) => output,
+                    ::core::option::Option::None => {
+                        ::core::unreachable!("A kind is always less than the QUANTITY.")
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        /// Names a single implementation, using its zero-based position in the order they were sent.\
+        /// Implementations sent with a name have a const of that name.
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub struct Kind(u16);
+
+        impl Kind {
+            /// The amount of kinds, which is the QUANTITY.
+            pub const COUNT: usize = QUANTITY as usize;
+
+            /// Every kind, in the order they were sent.
+            pub const ALL: [Kind; QUANTITY as usize] = {
+                let mut all = [Kind(0); QUANTITY as usize];
+                let mut index = 0;
+                while index < QUANTITY {
+                    all[index as usize] = Kind(index);
+                    index += 1;
+                }
+                all
+            };
+
+            /// The zero-based position of this kind's implementation, in the order they were sent.
+            pub const fn index(self) -> u16 {
+                self.0
+            }
+
+            /// The kind at the zero-based index, or None if the index is not less than the QUANTITY.
+            pub const fn from_index(index: u16) -> ::core::option::Option<Kind> {
+                if index < QUANTITY {
+                    ::core::option::Option::Some(Kind(index))
+                } else {
+                    ::core::option::Option::None
+                }
+            }
+
+            #dispatch
+
+            #(#const_accessors)*
+            #(#fn_accessors)*
+        }
+    })
+}
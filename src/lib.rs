@@ -2,31 +2,192 @@
 #![deny(clippy::unwrap_used)]
 #![warn(clippy::pedantic)]
 
+mod explicit;
+mod for_each;
+mod forward;
+mod iterate;
+mod kind;
+mod registry;
+mod shared;
+mod switches;
+mod values;
+
 use proc_macro::TokenStream as StdTokenStream;
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
-use std::env::VarError;
+use quote::{quote, quote_spanned};
+use std::{
+    collections::BTreeMap,
+    env::VarError,
+    sync::{Mutex, PoisonError},
+};
 use syn::{
-    FnArg, Generics, Ident, ImplItem, ItemFn, ItemImpl, ItemTrait, LitInt, Pat, Path, Stmt, Token,
-    TraitItem, Type, Visibility, parse::Parse, parse_macro_input, spanned::Spanned,
+    Ident, ImplItem, Item, ItemFn, ItemImpl, ItemTrait, LitInt, LitStr, Path, Token, TraitItem,
+    Type, Visibility, parse::Parse, parse_macro_input, punctuated::Punctuated, spanned::Spanned,
 };
 
-/// Allows a number in the attribute, or goes with a default.
-/// Optionally allows ident= before the number, where ident can be any ident.
-struct NumberAttribute<const DEFAULT: u32>(u32);
+/// Allows the capacity, optionally with any ident= before it, id= followed by a string, and any flags, separated by commas.
+// Each flag is independent, so they can't be combined into an enum.
+#[allow(clippy::struct_excessive_bools)]
+struct RetrieveAttribute {
+    /// Defaults to the quantity found by retrieval-build, or else 1000.
+    capacity: Option<u32>,
+    /// Generate an array of every implementation's value, for each associated const.
+    collect_consts: bool,
+    /// Generate an array of every implementation's function pointer, for each associated function.
+    collect_fns: bool,
+    /// Generate Kind, which names each implementation.
+    kind: bool,
+    /// Generate Visitor and dispatch, which visits an implementation chosen at runtime.
+    dispatch: bool,
+    /// Every implementation is sent with its index, instead of being counted.
+    explicit: bool,
+    /// The id implementations are counted under, instead of the trait's name.
+    id: Option<LitStr>,
+}
 
-impl<const DEFAULT: u32> Parse for NumberAttribute<DEFAULT> {
+impl Parse for RetrieveAttribute {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        Ok(NumberAttribute(match input.parse::<Option<Ident>>()? {
-            Some(_) => {
-                input.parse::<Token![=]>()?;
-                input.parse::<LitInt>()?.base10_parse()?
+        let mut attribute = RetrieveAttribute {
+            capacity: None,
+            collect_consts: false,
+            collect_fns: false,
+            kind: false,
+            dispatch: false,
+            explicit: false,
+            id: None,
+        };
+
+        while !input.is_empty() {
+            // Enum is a keyword, so it is not an ident.
+            if input.parse::<Option<Token![enum]>>()?.is_some() {
+                attribute.kind = true;
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+                continue;
             }
-            None => match input.parse::<Option<LitInt>>()? {
-                Some(capacity) => capacity.base10_parse()?,
-                None => DEFAULT,
-            },
-        }))
+
+            match input.parse::<Option<Ident>>()? {
+                Some(ident) if ident == "id" && input.peek(Token![=]) => {
+                    input.parse::<Token![=]>()?;
+                    attribute.id = Some(input.parse()?);
+                }
+                Some(_) if input.peek(Token![=]) => {
+                    input.parse::<Token![=]>()?;
+                    attribute.capacity = Some(input.parse::<LitInt>()?.base10_parse()?);
+                }
+                Some(ident) if ident == "collect_consts" => attribute.collect_consts = true,
+                Some(ident) if ident == "collect_fns" => attribute.collect_fns = true,
+                Some(ident) if ident == "dispatch" => attribute.dispatch = true,
+                Some(ident) if ident == "explicit" => attribute.explicit = true,
+                Some(ident) => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "Expected a capacity, id, collect_consts, collect_fns, enum, dispatch, or explicit.",
+                    ));
+                }
+                None => attribute.capacity = Some(input.parse::<LitInt>()?.base10_parse()?),
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(attribute)
+    }
+}
+
+/// A counted retrieval trait that claimed an id.
+struct Claim {
+    file: String,
+    line: usize,
+    capacity: u32,
+}
+
+/// Every id claimed by a counted retrieval trait, per crate.
+static IDS: Mutex<BTreeMap<(String, String), Claim>> = Mutex::new(BTreeMap::new());
+
+fn crate_name() -> String {
+    std::env::var("CARGO_CRATE_NAME").unwrap_or_default()
+}
+
+/// Fails if another retrieval trait in the crate is counted under the same id, as they would share a count.\
+/// The same trait expanding again is fine, and updates the capacity.
+fn claim_id(id: &str, capacity: u32) -> syn::Result<()> {
+    let span = proc_macro::Span::call_site();
+    let (file, line) = (span.file(), span.line());
+
+    let mut ids = IDS.lock().unwrap_or_else(PoisonError::into_inner);
+    let claim = ids
+        .entry((crate_name(), id.to_string()))
+        .or_insert_with(|| Claim {
+            file: file.clone(),
+            line,
+            capacity,
+        });
+    if claim.file == file && claim.line == line {
+        claim.capacity = capacity;
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            Span::call_site(),
+            format!(
+                "The retrieval trait at {}:{} is already counted under the id `{id}`. Give one of them a different id, using id = \"...\", and send to it using the same id.",
+                claim.file, claim.line,
+            ),
+        ))
+    }
+}
+
+/// The capacity of the retrieval trait that claimed the id, if it has been expanded yet.
+fn claimed_capacity(id: &str) -> Option<u32> {
+    IDS.lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&(crate_name(), id.to_string()))
+        .map(|claim| claim.capacity)
+}
+
+/// The quantity found by retrieval-build if it scanned the retrieval trait, along with the capacity.\
+/// A scanned trait's capacity defaults to its quantity, as no more can be sent. Otherwise it defaults to 1000.
+fn scanned_capacity(capacity: Option<u32>) -> syn::Result<(Option<u16>, u32)> {
+    let scanned = registry::quantity()?;
+    Ok((scanned, capacity.or(scanned.map(u32::from)).unwrap_or(1000)))
+}
+
+/// The trait's name, as written at the end of its path.
+fn last_ident(trait_path: &Path) -> syn::Result<&Ident> {
+    Ok(&trait_path
+        .segments
+        .last()
+        .ok_or(syn::Error::new(
+            trait_path.span(),
+            "Expected the retrieval trait path to contain at least one segment.",
+        ))?
+        .ident)
+}
+
+/// Generates the id that implementations are counted under, and a const check that sends can use to make sure they were counted under it.
+fn id_internal(id: &str) -> TokenStream {
+    quote! {
+        /// The id that implementations are counted under.
+        pub const ID: &str = #id;
+
+        /// Is this the id that implementations are counted under?
+        pub const fn is_id(id: &str) -> bool {
+            let (id, expected) = (id.as_bytes(), ID.as_bytes());
+            if id.len() != expected.len() {
+                return false;
+            }
+            let mut index = 0;
+            while index < id.len() {
+                if id[index] != expected[index] {
+                    return false;
+                }
+                index += 1;
+            }
+            true
+        }
     }
 }
 
@@ -35,10 +196,86 @@ fn internal_path(trait_path: &Path) -> Result<Path, syn::Error> {
 }
 
 /// Place on a trait to turn it into a retrieval trait, which is capable of collecting implementations.\
-/// Optionally allows the capacity to be specified. Defaults to 1000. The higher the capacity, the longer it will take to compile.
+/// Optionally allows the capacity to be specified. Defaults to 1000, or to the exact quantity if [retrieval-build](https://docs.rs/retrieval-build/) scanned the trait.\
+/// The higher the capacity, the longer it will take to compile, unless the crate uses [`shared_switches`](macro@shared_switches). Sending more implementations than the capacity is a compile error, which says how many were sent.
+///
+/// Can also be placed on a struct or enum, to collect values of it that are [sent](macro@send) as consts.\
+/// The type gets the QUANTITY of values, as well as ALL of them in the order they were sent.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve]
+/// struct Route {
+///     path: &'static str,
+///     handler: fn(),
+/// }
+///
+/// #[send]
+/// const _: Route = Route {
+///     path: "/",
+///     handler: || println!("Home!"),
+/// };
+///
+/// const ROUTES: [Route; Route::QUANTITY as usize] = Route::ALL;
+/// ```
 ///
 /// The trait becomes a module. It contains the QUANTITY of implementations as well as the actual Trait.
 ///
+/// With `dispatch`, the module also contains dispatch, which visits an implementation chosen at runtime with a Visitor. Kind gets a dispatch method too.\
+/// With `collect_consts`, the module also contains an ALL_ array for every associated const, holding each implementation's value in the order they were sent.\
+/// With `collect_fns`, the module also contains an array for every associated function, named after it in upper case, holding each implementation's function pointer.\
+/// An array whose name is already taken in the module, such as by QUANTITY or another array, is a compile error.\
+/// With `enum`, the module also contains Kind, a plain Copy value naming each implementation, which can be matched against names given by [send](macro@send).
+/// Kind has an accessor for every collected const and function, so `enum` implies both `collect_consts` and `collect_fns`.\
+/// A const's accessor is its name in lower case, and a function's is its own name, so a const and a function whose accessors would clash is a compile error.\
+/// With `explicit`, every implementation must be [sent](macro@send) with `index = N`, rather than being counted in the order they were sent.\
+/// This allows implementations to be sent from anywhere, in any order. Every index from 1 up to the highest must be sent exactly once, or it won't compile.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve(explicit, 10)]
+/// trait Step {
+///     const NAME: &str = "";
+/// }
+///
+/// #[send(index = 2)]
+/// impl Step {
+///     const NAME: &str = "second";
+/// }
+///
+/// #[send(index = 1)]
+/// impl Step {
+///     const NAME: &str = "first";
+/// }
+/// ```
+///
+/// Alternatively, a build script can use [retrieval-build](https://docs.rs/retrieval-build/) to assign every index before the crate is compiled.\
+/// Implementations are then indexed in the order they are written, regardless of the order macros are expanded in.
+///
+/// Otherwise, implementations are counted under the trait's name, so two counted retrieval traits in a crate can't share a name.\
+/// With `id = "..."`, they are counted under the id instead, which must then be given to every [send](macro@send) as well.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// mod chat {
+///     # use retrieval::*;
+///     #[retrieve(id = "chat")]
+///     pub trait Message {
+///         const STR: &str = "";
+///     }
+/// }
+///
+/// #[retrieve]
+/// trait Message {
+///     const STR: &str = "";
+/// }
+///
+/// #[send(id = "chat")]
+/// impl chat::Message {
+///     const STR: &str = "Hello chat!";
+/// }
+/// ```
+///
 /// Due to how this works internally, there are a few restrictions:\
 /// All associated items must have a default. (Allows associated types to have defaults, which would normally not be allowed.)
 /// ```rust
@@ -58,19 +295,78 @@ fn internal_path(trait_path: &Path) -> Result<Path, syn::Error> {
 ///
 /// #[retrieve(1000)]
 /// trait Empty {}
+///
+/// #[retrieve(capacity = 10, collect_consts, dispatch)]
+/// trait Named {
+///     const NAME: &str = "";
+/// }
+/// const NAMES: [&str; Named::QUANTITY as usize] = Named::ALL_NAME;
+///
+/// #[retrieve(collect_fns)]
+/// trait Handler {
+///     fn handle(input: &str) -> bool {
+///         false
+///     }
+/// }
+/// fn handle(index: usize, input: &str) -> bool {
+///     Handler::HANDLE[index](input)
+/// }
+///
+/// struct GetName;
+/// impl Named::Visitor for GetName {
+///     type Output = &'static str;
+///     fn visit<T: Named::Trait>(self) -> &'static str {
+///         T::NAME
+///     }
+/// }
+/// fn menu_selection(index: u16) -> Option<&'static str> {
+///     Named::dispatch(index, GetName)
+/// }
+///
+/// #[retrieve(enum)]
+/// trait Shape {
+///     const SIDES: u8 = 0;
+/// }
+/// fn describe(shape: Shape::Kind) -> String {
+///     format!("Shape {} has {} sides.", shape.index(), shape.sides())
+/// }
 /// ```
 #[proc_macro_attribute]
 pub fn retrieve(input: StdTokenStream, item: StdTokenStream) -> StdTokenStream {
-    let item = parse_macro_input!(item as ItemTrait);
-    retrieve_internal(input.into(), item)
-        .unwrap_or_else(syn::Error::into_compile_error)
-        .into()
+    let item = parse_macro_input!(item as Item);
+    match item {
+        Item::Trait(item) => retrieve_internal(input.into(), item),
+        Item::Struct(item) => {
+            values::retrieve_value_internal(input.into(), &item.ident, &item.vis, &item.generics)
+                .map(|registry| quote! {#item #registry})
+        }
+        Item::Enum(item) => {
+            values::retrieve_value_internal(input.into(), &item.ident, &item.vis, &item.generics)
+                .map(|registry| quote! {#item #registry})
+        }
+        item => Err(syn::Error::new(
+            item.span(),
+            "Expected a trait, or a struct or enum whose values are retrieved.",
+        )),
+    }
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
 }
 
 fn retrieve_internal(input: TokenStream, mut item: ItemTrait) -> syn::Result<TokenStream> {
-    let capacity = syn::parse2::<NumberAttribute<1000>>(input)?.0;
+    let RetrieveAttribute {
+        capacity,
+        collect_consts,
+        collect_fns,
+        kind,
+        dispatch,
+        explicit,
+        id,
+    } = syn::parse2(input)?;
+    let (scanned, capacity) = scanned_capacity(capacity)?;
 
     let trait_ident = std::mem::replace(&mut item.ident, Ident::new("Trait", Span::call_site()));
+    let id = id.map_or_else(|| trait_ident.to_string(), |id| id.value());
     let trait_vis = std::mem::replace(&mut item.vis, Visibility::Public(<Token![pub]>::default()));
 
     // Associated types aren't allowed defaults, so we remove them, and pass the default to our initial implementation.
@@ -91,22 +387,49 @@ fn retrieve_internal(input: TokenStream, mut item: ItemTrait) -> syn::Result<Tok
         })
         .collect();
 
+    let dispatch = if dispatch {
+        dispatch_internal(capacity)?
+    } else {
+        TokenStream::new()
+    };
+
+    // Kind's accessors rely on the collected arrays.
+    let (collect_consts, collect_fns) = (collect_consts || kind, collect_fns || kind);
+    check_arrays(&item, collect_consts, collect_fns, !dispatch.is_empty())?;
+    let mut collected = TokenStream::new();
+    if collect_consts {
+        collected.extend(collect_consts_internal(&item, capacity)?);
+    }
+    if collect_fns {
+        collected.extend(collect_fns_internal(&item, capacity)?);
+    }
+    if kind {
+        collected.extend(kind::kind_internal(&item, !dispatch.is_empty())?);
+    }
+
     item.items.push(TraitItem::Verbatim(quote! {
         /// The next type in the chain.
         type NEXT: Trait;
 
         /// Is this the end of the chain?
         const END: bool = false;
+
+        /// The index of this implementation, starting from 1 for the first one sent.\
+        /// The end of the chain is 0.
+        const INDEX: u16;
+
+        /// The type that was sent.\
+        /// This is a named type if one was sent, otherwise it is Self.
+        type ITEM: Trait + ?Sized;
     }));
 
-    // One extra for the one required impl.
-    let setup = replaceable_implementations::setup(capacity + 1);
-    let path_to_setup: Path = syn::parse2(quote! {#trait_ident::__internal})?;
-    let initial_implementation = replaceable_implementations::initial_implementation(
-        &path_to_setup,
-        syn::parse2(quote! {
-            impl #trait_ident::__internal::Final for #trait_ident::__internal::Container<0> {}
-        })?,
+    // Traits scanned by retrieval-build have every index assigned, just like explicit ones.
+    let (setup, initial_implementation) = setup_internal(
+        &mut item,
+        &trait_ident,
+        &id,
+        capacity,
+        explicit || scanned.is_some(),
     )?;
 
     let output = quote! {
@@ -128,6 +451,10 @@ fn retrieve_internal(input: TokenStream, mut item: ItemTrait) -> syn::Result<Tok
                 get_quantity()
             };
 
+            #dispatch
+
+            #collected
+
             #[doc(hidden)]
             /// !!! INTERNAL !!!
             /// If you are not a maintainer of the retrieval crate, then you are lost.
@@ -151,6 +478,8 @@ fn retrieve_internal(input: TokenStream, mut item: ItemTrait) -> syn::Result<Tok
             #(#default_types)*
             type NEXT = Self;
             const END: bool = true;
+            const INDEX: u16 = 0;
+            type ITEM = Self;
         }
         #initial_implementation
     };
@@ -158,244 +487,1258 @@ fn retrieve_internal(input: TokenStream, mut item: ItemTrait) -> syn::Result<Tok
     Ok(output)
 }
 
-/// Place on an inherent impl of a [retrieval trait](macro@retrieve) in order to send it for retrieval.
-/// ```
-/// # use retrieval::*;
-/// # fn main() {}
-/// #[retrieve]
-/// trait Message {
-///     const STR: &str = "";
-/// }
-///
-/// #[send]
-/// impl Message {
-///     const STR: &str = "Hello world!";
-/// }
-/// ```
-#[proc_macro_attribute]
-pub fn send(input: StdTokenStream, item: StdTokenStream) -> StdTokenStream {
-    let item = parse_macro_input!(item as ItemImpl);
-    send_internal(&input.into(), item)
-        .unwrap_or_else(syn::Error::into_compile_error)
-        .into()
-}
+/// Generates the switches that every implementation is chained through, and the implementation at the end of the chain.\
+/// Also generates the id that counted implementations check against.
+fn setup_internal(
+    item: &mut ItemTrait,
+    trait_ident: &Ident,
+    id: &str,
+    capacity: u32,
+    explicit: bool,
+) -> syn::Result<(TokenStream, TokenStream)> {
+    // One extra for the one required impl.
+    let path_to_setup: Path = syn::parse2(quote! {#trait_ident::__internal})?;
+    let (setup, initial_implementation) = if explicit {
+        item.attrs.push(syn::parse_quote! {
+            #[diagnostic::on_unimplemented(
+                message = "`{Self}` was not sent.",
+                note = "Every index from 1 up to the highest index sent must be sent exactly once."
+            )]
+        });
+        (
+            explicit::setup(capacity + 1),
+            explicit::implementation(&path_to_setup, 0),
+        )
+    } else {
+        claim_id(id, capacity)?;
+        match shared::implementation(&path_to_setup, id, 0)? {
+            // The switches are in the crate's pool.
+            Some(initial_implementation) => (quote! {}, initial_implementation),
+            None => (
+                replaceable_implementations::setup(capacity + 1),
+                replaceable_implementations::initial_implementation(
+                    &path_to_setup,
+                    syn::parse2(quote! {
+                        impl #trait_ident::__internal::Final for #trait_ident::__internal::Container<0> {}
+                    })?,
+                )?,
+            ),
+        }
+    };
+    let shared = !explicit && shared::enabled();
 
-fn send_internal(input: &TokenStream, mut item: ItemImpl) -> syn::Result<TokenStream> {
-    if !input.is_empty() {
-        return Err(syn::Error::new(
-            input.span(),
-            "This attribute accepts nothing but itself.",
-        ));
-    }
+    let id = id_internal(id);
+    let hop = iterate::capacity_hop(trait_ident, capacity);
+    Ok((
+        quote! {
+            /// The most implementations that can be sent.
+            pub const CAPACITY: u32 = #capacity;
+            #hop
 
-    let Type::Path(trait_path) = &*item.self_ty else {
-        return Err(syn::Error::new(
-            item.self_ty.span(),
-            "The inherent impl's type is not a retrieval trait, which doesn't make sense.",
-        ));
-    };
-    let trait_path = trait_path.path.clone();
-    let internal_path = internal_path(&trait_path)?;
-    let trait_ident_string = trait_path
-        .segments
-        .last()
-        .ok_or(syn::Error::new(
-            trait_path.span(),
-            "Expected the retrieval trait path to contain at least one segment.",
-        ))?
-        .ident
-        .to_string();
+            /// Are the switches in the crate's pool?
+            pub const SHARED: bool = #shared;
+            #id
+            #setup
+        },
+        initial_implementation,
+    ))
+}
 
-    let (previous_implementations, replace) =
-        replaceable_implementations::replace_implementation(
-            &internal_path,
-            trait_ident_string,
-            true,
-        )
-        .map_err(|err| match err {
-            VarError::NotPresent => syn::Error::new(
-                Span::call_site(),
-                "The crate name was not present in the environment variables.",
-            ),
-            VarError::NotUnicode(crate_name) => syn::Error::new(
-                Span::call_site(),
-                format!(
-                    "The crate name was not unicode. Crate name: {}",
-                    crate_name.display(),
-                ),
-            ),
-        })?;
-    let implementation = replace(syn::parse2(quote! {
-        impl #internal_path::Final for #internal_path::Container<#previous_implementations> {}
-    })?);
+/// Generates the Visitor trait, and the dispatch function that visits an implementation chosen at runtime.
+fn dispatch_internal(capacity: u32) -> syn::Result<TokenStream> {
+    let find = iterate::iterate_internal(
+        quote! {#capacity, order = forward},
+        syn::parse2(quote! {
+            fn find<T: self, V: Visitor>(
+                visitor: &mut ::core::option::Option<V>,
+                index: u16,
+                #[position] position: u16,
+            ) -> ::core::ops::ControlFlow<V::Output> {
+                match visitor.take() {
+                    ::core::option::Option::Some(found) if position == index => {
+                        ::core::ops::ControlFlow::Break(found.visit::<T>())
+                    }
+                    other => {
+                        *visitor = other;
+                        ::core::ops::ControlFlow::Continue(())
+                    }
+                }
+            }
+        })?,
+    )?;
 
-    let current = LitInt::new(&(previous_implementations).to_string(), Span::call_site());
-    let next = LitInt::new(
-        &(previous_implementations - 1).to_string(),
-        Span::call_site(),
-    );
+    Ok(quote! {
+        /// Visits a single implementation, chosen at runtime using [dispatch].
+        pub trait Visitor {
+            /// What visiting returns.
+            type Output;
 
-    *item.self_ty = Type::Verbatim(quote! {#trait_path::__internal::Container<#current>});
+            /// Called with the chosen implementation.
+            fn visit<T: Trait>(self) -> Self::Output;
+        }
 
-    item.trait_ = Some((
-        None,
-        syn::parse2(quote! {#trait_path::Trait})?,
-        <Token![for]>::default(),
-    ));
+        /// Visits the implementation at the zero-based index, in the order they were sent.\
+        /// Returns None if the index is not less than the QUANTITY.
+        pub fn dispatch<V: Visitor>(index: u16, visitor: V) -> ::core::option::Option<V::Output> {
+            #find
 
-    item.items.push(ImplItem::Verbatim(quote! {
-        type NEXT = #trait_path::__internal::Container<#next>;
-    }));
+            if index >= QUANTITY {
+                return ::core::option::Option::None;
+            }
+            find(&mut ::core::option::Option::Some(visitor), index)
+        }
+    })
+}
 
-    let output = quote! {
-        #item
-        #implementation
-    };
+/// Generates an array containing each implementation's associated item, in the order they were sent.
+fn collect_array(
+    capacity: u32,
+    array_ident: &Ident,
+    doc: &str,
+    ty: &TokenStream,
+    item_ident: &Ident,
+) -> syn::Result<TokenStream> {
+    let collect = iterate::iterate_internal(
+        quote! {#capacity, order = forward},
+        syn::parse2(quote! {
+            const fn collect<T: self>(all: &mut [#ty], #[position] position: usize) {
+                // The default is forgotten rather than dropped, as it may not be droppable in const.
+                ::core::mem::forget(::core::mem::replace(&mut all[position], T::#item_ident));
+            }
+        })?,
+    )?;
 
-    Ok(output)
+    Ok(quote! {
+        #[doc = #doc]
+        pub const #array_ident: [#ty; QUANTITY as usize] = {
+            #collect
+
+            let mut all: [#ty; QUANTITY as usize] =
+                [const { <__internal::Container<0> as Trait>::#item_ident }; QUANTITY as usize];
+            collect(&mut all);
+            all
+        };
+    })
 }
 
-/// Place on a generic function to convert it into a non-generic function that iterates through all implementations of a [retrieval trait](macro@retrieve).\
-/// Optionally allows the capacity to be specified. Defaults to 1000. The higher the capacity, the longer it will take to compile.
-///
-/// Due to [an issue](https://github.com/coolcatcoder/retrieval/issues/7) the function must have only one generic with only one trait bound.
-/// ```rust
-/// # use retrieval::*;
-/// # fn main() {}
-/// #[retrieve]
-/// trait Message {
-///     const STR: &str = "";
-/// }
-///
-/// #[iterate]
-/// const fn collect_messages<T: Message>(messages: &mut [&str], index: &mut usize) {
-///     messages[*index] = T::STR;
-///     *index += 1;
-/// }
-///
-/// const MESSAGES: [&str; Message::QUANTITY as usize] = {
-///     let mut messages = [""; Message::QUANTITY as usize];
-///     let mut index = 0;
-///
-///     collect_messages(&mut messages, &mut index);
-///     messages
-/// };
-/// ```
-#[proc_macro_attribute]
-pub fn iterate(input: StdTokenStream, item: StdTokenStream) -> StdTokenStream {
-    let item = parse_macro_input!(item as ItemFn);
-    iterate_internal(input.into(), item)
-        .unwrap_or_else(syn::Error::into_compile_error)
-        .into()
+/// The associated consts that can be collected into an array.\
+/// Consts that mention Self are skipped, as their type may be different for each implementation.
+fn collectable_consts(item: &ItemTrait) -> impl Iterator<Item = &syn::TraitItemConst> {
+    item.items.iter().filter_map(|item| {
+        let TraitItem::Const(item) = item else {
+            return None;
+        };
+        let ty = &item.ty;
+        (item.generics.params.is_empty()
+            && !iterate::mentions(quote! {#ty}, &Ident::new("Self", Span::call_site())))
+        .then_some(item)
+    })
 }
 
-fn iterate_internal(input: TokenStream, mut internal: ItemFn) -> syn::Result<TokenStream> {
-    // Work out how many functions we will need to reach the target recursion limit.
-    let recursion_limit = syn::parse2::<NumberAttribute<1000>>(input)?.0;
-    let functions_needed = recursion_limit.div_ceil(128);
+/// The associated functions that can be collected into an array, along with their function pointer type.\
+/// Functions that can't be a function pointer are skipped, such as methods, or those that are generic, async, or mention Self.
+fn collectable_fns(item: &ItemTrait) -> impl Iterator<Item = (&syn::Signature, TokenStream)> {
+    item.items.iter().filter_map(|item| {
+        let TraitItem::Fn(item) = item else {
+            return None;
+        };
+        let sig = &item.sig;
+        if sig.receiver().is_some()
+            || !sig.generics.params.is_empty()
+            || sig.asyncness.is_some()
+            || sig.variadic.is_some()
+            || iterate::mentions(quote! {#sig}, &Ident::new("Self", Span::call_site()))
+            || iterate::mentions(quote! {#sig}, &Ident::new("impl", Span::call_site()))
+        {
+            return None;
+        }
 
-    if internal.sig.generics.params.len() != 1 {
-        return Err(syn::Error::new(
-            internal.sig.generics.span(),
-            "Only one generic is supported at this time.\nPlease see https://github.com/coolcatcoder/retrieval/issues/7.",
-        ));
+        let unsafety = &sig.unsafety;
+        let abi = &sig.abi;
+        let inputs = sig.inputs.iter().filter_map(|input| match input {
+            syn::FnArg::Typed(input) => Some(&*input.ty),
+            syn::FnArg::Receiver(_) => None,
+        });
+        let output = &sig.output;
+
+        Some((sig, quote! {#unsafety #abi fn(#(#inputs),*) #output}))
+    })
+}
+
+/// Fails if a collected array would have the same name as another item in the trait's module.\
+/// Const arrays are ALL_ followed by the const's name, and fn arrays are the fn's name in upper case, so they can clash.
+fn check_arrays(
+    item: &ItemTrait,
+    collect_consts: bool,
+    collect_fns: bool,
+    dispatch: bool,
+) -> syn::Result<()> {
+    let mut taken = BTreeMap::from([
+        ("Trait".to_string(), "the trait itself".to_string()),
+        ("QUANTITY".to_string(), "the trait's QUANTITY".to_string()),
+        ("Kind".to_string(), "the trait's Kind".to_string()),
+    ]);
+    if dispatch {
+        taken.insert("Visitor".to_string(), "dispatch's Visitor".to_string());
+        taken.insert("dispatch".to_string(), "dispatch itself".to_string());
     }
 
-    let module_path: syn::Path = {
-        let type_param_span = internal.sig.generics.span();
-        let generic = internal
-            .sig
-            .generics
-            .type_params_mut()
-            .next()
-            .ok_or(syn::Error::new(
-                type_param_span,
-                "Expected a single type param.",
-            ))?;
-        let syn::TypeParamBound::Trait(trait_bound) = generic
-            .bounds
-            .first_mut()
-            .ok_or(syn::Error::new(type_param_span, "Expected a bound."))?
-        else {
+    let consts = collectable_consts(item)
+        .filter(|_| collect_consts)
+        .map(|item| (&item.ident, format!("ALL_{}", item.ident), "const"));
+    let fns = collectable_fns(item)
+        .filter(|_| collect_fns)
+        .map(|(sig, _)| (&sig.ident, sig.ident.to_string().to_uppercase(), "fn"));
+    for (ident, array, kind) in consts.chain(fns) {
+        if let Some(owner) = taken.get(&array) {
             return Err(syn::Error::new(
-                generic.bounds.span(),
-                "The singular generic should only have one trait bound.\nPlease see https://github.com/coolcatcoder/retrieval/issues/7.",
+                ident.span(),
+                format!(
+                    "The array of {kind} `{ident}` would be called `{array}`, which is already {owner}. Rename one of them."
+                ),
             ));
-        };
-        let module_path = trait_bound.path.clone();
-        trait_bound.path.segments.push(syn::parse2(quote! {Trait})?);
-        module_path
-    };
-    let generic_ident = &internal
-        .sig
-        .generics
-        .type_params()
-        .next()
-        .expect("TO DO: Come up with a good reason why I expect this to never fail.")
-        .ident;
-
-    // Get the module from the last segment of the trait bound.
-    //let trait_ident = &trait_bound.path.segments.last().unwrap().ident;
-
-    // Create the external function's signature from the internal's but without the generics.
-    let mut external_sig = internal.sig.clone();
-    let external_vis = internal.vis.clone();
-    external_sig.generics = Generics::default();
-    let external_ident = &external_sig.ident;
-    let inputs: Vec<&Pat> = external_sig
-        .inputs
-        .iter()
-        .map(|input| {
-            let FnArg::Typed(input) = input else {
-                panic!("Cannot have self input. That doesn't make sense.");
-            };
-
-            &*input.pat
-        })
-        .collect();
-
-    // The first internal function's ident.
-    let internal_start_ident =
-        Ident::new(&format!("__internal_0_{external_ident}"), Span::call_site());
-
-    let mut output = quote! {
-        #external_vis #external_sig {
-            #internal_start_ident::<#module_path::__internal::Container<{#module_path::QUANTITY}>>(#(#inputs),*);
         }
-    };
+        taken.insert(array, format!("the array of {kind} `{ident}`"));
+    }
 
-    let if_end: Stmt = syn::parse2(quote! {
-        if #generic_ident::END {
-            return;
-        }
-    })?;
-    internal.vis = Visibility::Inherited;
+    Ok(())
+}
 
-    for index in 0..functions_needed {
-        let next_index = if index == functions_needed - 1 {
-            0
-        } else {
-            index + 1
-        };
-        let mut internal = internal.clone();
+/// For every associated const, generates an array containing each implementation's value, in the order they were sent.
+fn collect_consts_internal(item: &ItemTrait, capacity: u32) -> syn::Result<TokenStream> {
+    let mut output = TokenStream::new();
 
-        internal.sig.ident = Ident::new(
-            &format!("__internal_{}_{}", index, &external_ident),
-            Span::call_site(),
-        );
+    for item in collectable_consts(item) {
+        let ident = &item.ident;
+        let ty = &item.ty;
+        output.extend(collect_array(
+            capacity,
+            &Ident::new(&format!("ALL_{ident}"), ident.span()),
+            &format!("Every implementation's {ident}, in the order they were sent."),
+            &quote! {#ty},
+            ident,
+        )?);
+    }
+
+    Ok(output)
+}
 
-        internal.block.stmts.insert(0, if_end.clone());
+/// For every associated function, generates an array containing each implementation's function pointer, in the order they were sent.\
+/// The array is named after the function, in upper case.
+fn collect_fns_internal(item: &ItemTrait, capacity: u32) -> syn::Result<TokenStream> {
+    let mut output = TokenStream::new();
 
-        let internal_next_ident = Ident::new(
-            &format!("__internal_{next_index}_{external_ident}"),
-            Span::call_site(),
-        );
-        internal.block.stmts.push(syn::parse2(
-            quote! {#internal_next_ident::<#generic_ident::NEXT>(#(#inputs),*);},
+    for (sig, ty) in collectable_fns(item) {
+        let ident = &sig.ident;
+        output.extend(collect_array(
+            capacity,
+            &Ident::new(&ident.to_string().to_uppercase(), ident.span()),
+            &format!("Every implementation's {ident}, in the order they were sent."),
+            &ty,
+            ident,
         )?);
-
-        output.extend(quote! {#internal});
     }
 
     Ok(output)
 }
+
+/// Place on an inherent impl of a [retrieval trait](macro@retrieve) in order to send it for retrieval.
+///
+/// Can also be placed on a const whose type [collects values](macro@retrieve), to send its value. The const can be named, or just _.
+///
+/// Starting with `for Ident in [Type, ...]` sends a copy for every type, with the ident replaced by that type.\
+/// It can't be combined with `name =` or `index =`, as every copy would be given the same one.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve]
+/// trait Codec {
+///     type Ty = ();
+///     const NAME: &str = "";
+/// }
+///
+/// #[send(for T in [u8, u16, u32, u64])]
+/// impl Codec {
+///     type Ty = T;
+///     const NAME: &str = stringify!(T);
+/// }
+/// ```
+///
+/// `also = [Trait, Other { ITEM, item }]` sends a copy of the impl to other retrieval traits, each getting its own INDEX.\
+/// Listing idents in braces sends only those items. Options for a trait go in parentheses after it, such as `Other(id = "other")` or `Other(index = 2) { ITEM }`.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve]
+/// trait Command {
+///     const NAME: &str = "";
+///     fn run() {}
+/// }
+///
+/// #[retrieve]
+/// trait HelpTopic {
+///     const NAME: &str = "";
+/// }
+///
+/// #[send(also = [HelpTopic { NAME }])]
+/// impl Command {
+///     const NAME: &str = "quit";
+///     fn run() {
+///         std::process::exit(0);
+///     }
+/// }
+/// ```
+///
+/// Can also be placed on a function, given the retrieval trait to send it to. The function is kept, and the trait's `call` function calls it.\
+/// `fn = Ident` chooses a different trait function, and `const IDENT: Type = value` gives the implementation a const.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve]
+/// trait Handler {
+///     const NAME: &str = "";
+///     fn call() {}
+/// }
+///
+/// #[send(Handler, const NAME: &str = "start")]
+/// fn on_start() {
+///     println!("Started!");
+/// }
+/// ```
+///
+/// Each implementation is given an INDEX, starting from 1 for the first one sent.\
+/// If the retrieval trait is `explicit`, then `index = N` must be given instead, and becomes the INDEX.\
+/// If the retrieval trait has an id, or is sent through a renamed import, then `id = "..."` must give the trait's id.\
+/// If the retrieval trait has a Kind, then `name = Ident` gives the implementation's Kind a const of that name.
+///
+/// A named type can be sent by implementing the retrieval trait for it. The type must be declared `pub`, although it can be in a private module, as it becomes the ITEM of a public container.\
+/// Sending a private type fails with "private type in public interface", pointing at the type in the send.\
+/// Its container's ITEM is the named type, so iteration can use the type itself. Other implementations have themselves as their ITEM.\
+/// The container forwards everything it can to the named type. Methods, and functions that mention Self, are only available through ITEM.
+/// ```
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve]
+/// trait Message {
+///     const STR: &str = "";
+/// }
+///
+/// #[send]
+/// impl Message {
+///     const STR: &str = "Hello world!";
+/// }
+///
+/// #[send]
+/// impl Message {
+///     const STR: &str = if Self::INDEX == 2 { "Second!" } else { "" };
+/// }
+///
+/// #[retrieve(enum)]
+/// trait Command {
+///     const HELP: &str = "";
+/// }
+///
+/// #[send(name = Quit)]
+/// impl Command {
+///     const HELP: &str = "Stops the program.";
+/// }
+///
+/// fn is_quit(command: Command::Kind) -> bool {
+///     matches!(command, Command::Kind::Quit)
+/// }
+///
+/// #[retrieve]
+/// trait Plugin {
+///     fn build(&self) -> String {
+///         String::new()
+///     }
+/// }
+///
+/// #[derive(Default)]
+/// pub struct Window;
+///
+/// #[send]
+/// impl Plugin for Window {
+///     fn build(&self) -> String {
+///         "Built a window!".to_owned()
+///     }
+/// }
+///
+/// #[iterate]
+/// fn build_all<T: Plugin>(built: &mut Vec<String>)
+/// where
+///     T::ITEM: Default + Sized,
+/// {
+///     use Plugin::Trait as _;
+///     built.push(T::ITEM::default().build());
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn send(input: StdTokenStream, item: StdTokenStream) -> StdTokenStream {
+    let Template { template, input } = parse_macro_input!(input as Template);
+    let item = TokenStream::from(item);
+
+    match template {
+        Some((ident, types)) => types
+            .iter()
+            .enumerate()
+            .map(|(copy, ty)| {
+                let item = syn::parse2(substitute(item.clone(), &ident, &quote! {#ty}))?;
+                send_item(&input, item, copy)
+            })
+            .collect(),
+        None => syn::parse2(item).and_then(|item| send_item(&input, item, 0)),
+    }
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Sends the item, which is the given copy of a template, or copy 0.
+fn send_item(input: &TokenStream, item: Item, copy: usize) -> syn::Result<TokenStream> {
+    match item {
+        Item::Impl(item) => send_internal(input, item, copy),
+        Item::Const(item) => values::send_value_internal(input, &item, copy),
+        Item::Fn(item) => send_fn_internal(input.clone(), &item, copy),
+        item => Err(syn::Error::new(
+            item.span(),
+            "Expected an impl of a retrieval trait, a const whose value is retrieved, or a function.",
+        )),
+    }
+}
+
+/// Optionally allows the send attribute to start with `for Ident in [Type, ...]`.\
+/// The rest of the attribute is left alone.
+struct Template {
+    template: Option<(Ident, Vec<Type>)>,
+    input: TokenStream,
+}
+
+impl Parse for Template {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let template = if input.parse::<Option<Token![for]>>()?.is_some() {
+            let ident = input.parse::<Ident>()?;
+            input.parse::<Token![in]>()?;
+            let types;
+            syn::bracketed!(types in input);
+            let types = Punctuated::<Type, Token![,]>::parse_terminated(&types)?
+                .into_iter()
+                .collect();
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+            Some((ident, types))
+        } else {
+            None
+        };
+        let input: TokenStream = input.parse()?;
+
+        // Every copy would get the same name or index.
+        if template.is_some()
+            && let Some(ident) = shared_by_copies(input.clone())
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "Every copy of the template would be given the same {ident}. Send the types separately to give each its own."
+                ),
+            ));
+        }
+
+        Ok(Template { template, input })
+    }
+}
+
+/// Finds a `name =` or `index =` in the send options, including those of `also` targets.
+fn shared_by_copies(options: TokenStream) -> Option<Ident> {
+    use proc_macro2::{Delimiter, TokenTree};
+
+    let tokens: Vec<_> = options.into_iter().collect();
+    tokens.windows(3).find_map(|window| match window {
+        [TokenTree::Ident(ident), TokenTree::Punct(punct), _]
+            if punct.as_char() == '='
+                && punct.spacing() == proc_macro2::Spacing::Alone
+                && (ident == "name" || ident == "index") =>
+        {
+            Some(ident.clone())
+        }
+        [
+            TokenTree::Ident(ident),
+            TokenTree::Punct(_),
+            TokenTree::Group(targets),
+        ] if ident == "also" && targets.delimiter() == Delimiter::Bracket => targets
+            .stream()
+            .into_iter()
+            .find_map(|target| match target {
+                TokenTree::Group(options) if options.delimiter() == Delimiter::Parenthesis => {
+                    shared_by_copies(options.stream())
+                }
+                _ => None,
+            }),
+        _ => None,
+    })
+}
+
+/// Replaces every occurrence of the ident with the replacement.
+fn substitute(tokens: TokenStream, ident: &Ident, replacement: &TokenStream) -> TokenStream {
+    tokens
+        .into_iter()
+        .flat_map(|token| match token {
+            proc_macro2::TokenTree::Ident(token) if token == *ident => replacement.clone(),
+            proc_macro2::TokenTree::Group(group) => {
+                let mut substituted = proc_macro2::Group::new(
+                    group.delimiter(),
+                    substitute(group.stream(), ident, replacement),
+                );
+                substituted.set_span(group.span());
+                quote! {#substituted}
+            }
+            token => quote! {#token},
+        })
+        .collect()
+}
+
+/// Optionally allows name= followed by the name of the implementation's Kind.\
+/// When sending a function, also allows the retrieval trait, fn= followed by the trait's function, and consts.
+struct SendAttribute {
+    name: Option<Ident>,
+    /// The index of an implementation sent to a retrieval trait in explicit mode.
+    index: Option<u16>,
+    /// The id the implementation is counted under, instead of the trait's name.
+    id: Option<LitStr>,
+    /// The retrieval trait a function is sent to.
+    trait_path: Option<Path>,
+    /// The trait's function that a sent function becomes.
+    fn_ident: Option<Ident>,
+    /// Consts added to the implementation of a sent function.
+    consts: Vec<TokenStream>,
+    /// Other retrieval traits that an impl is also sent to.
+    also: Vec<AlsoTarget>,
+}
+
+/// Another retrieval trait that an impl is also sent to.
+struct AlsoTarget {
+    path: Path,
+    /// The send options used for this trait, such as its id or index.
+    options: TokenStream,
+    /// The idents of the items it receives, otherwise it receives every item.
+    projection: Option<Vec<Ident>>,
+}
+
+impl Parse for SendAttribute {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut attribute = SendAttribute {
+            name: None,
+            index: None,
+            id: None,
+            trait_path: None,
+            fn_ident: None,
+            consts: vec![],
+            also: vec![],
+        };
+
+        while !input.is_empty() {
+            if input.peek(Token![const]) {
+                input.parse::<Token![const]>()?;
+                let ident = input.parse::<Ident>()?;
+                input.parse::<Token![:]>()?;
+                let ty = input.parse::<Type>()?;
+                input.parse::<Token![=]>()?;
+                let expr = input.parse::<syn::Expr>()?;
+                attribute.consts.push(quote! {const #ident: #ty = #expr;});
+            } else if input.peek(Token![fn]) {
+                input.parse::<Token![fn]>()?;
+                input.parse::<Token![=]>()?;
+                attribute.fn_ident = Some(input.parse()?);
+            } else if input.peek(Ident) && input.peek2(Token![=]) {
+                let ident = input.parse::<Ident>()?;
+                input.parse::<Token![=]>()?;
+                if ident == "name" {
+                    attribute.name = Some(input.parse()?);
+                } else if ident == "index" {
+                    let index = input.parse::<LitInt>()?;
+                    if index.base10_parse::<u16>()? == 0 {
+                        return Err(syn::Error::new(
+                            index.span(),
+                            "Indices start from 1, as 0 is the end of the chain.",
+                        ));
+                    }
+                    attribute.index = Some(index.base10_parse()?);
+                } else if ident == "id" {
+                    attribute.id = Some(input.parse()?);
+                } else if ident == "also" {
+                    let targets;
+                    syn::bracketed!(targets in input);
+                    while !targets.is_empty() {
+                        let target = Path::parse_mod_style(&targets)?;
+                        let options = if targets.peek(syn::token::Paren) {
+                            let options;
+                            syn::parenthesized!(options in targets);
+                            options.parse()?
+                        } else {
+                            TokenStream::new()
+                        };
+                        let projection = if targets.peek(syn::token::Brace) {
+                            let items;
+                            syn::braced!(items in targets);
+                            Some(
+                                Punctuated::<Ident, Token![,]>::parse_terminated(&items)?
+                                    .into_iter()
+                                    .collect(),
+                            )
+                        } else {
+                            None
+                        };
+                        attribute.also.push(AlsoTarget {
+                            path: target,
+                            options,
+                            projection,
+                        });
+
+                        if !targets.is_empty() {
+                            targets.parse::<Token![,]>()?;
+                        }
+                    }
+                } else {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        "Expected name, index, id, or also.",
+                    ));
+                }
+            } else {
+                let trait_path = input.parse::<Path>()?;
+                if attribute.trait_path.is_some() {
+                    return Err(syn::Error::new(
+                        trait_path.span(),
+                        "Only one retrieval trait can be given.",
+                    ));
+                }
+                attribute.trait_path = Some(trait_path);
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(attribute)
+    }
+}
+
+/// Sends a function, by sending an implementation whose function calls it.\
+/// The function is kept, so it can still be called by name.
+fn send_fn_internal(input: TokenStream, item: &ItemFn, copy: usize) -> syn::Result<TokenStream> {
+    let SendAttribute {
+        name,
+        index,
+        id,
+        trait_path,
+        fn_ident,
+        consts,
+        also,
+    } = syn::parse2(input)?;
+    if let Some(target) = also.first() {
+        return Err(syn::Error::new(
+            target.path.span(),
+            "Only an impl can be sent to other retrieval traits.",
+        ));
+    }
+    let trait_path = trait_path.ok_or(syn::Error::new(
+        item.sig.span(),
+        "Expected the retrieval trait to send this function to, such as #[send(Handlers)].",
+    ))?;
+    if let Some(receiver) = item.sig.receiver() {
+        return Err(syn::Error::new(
+            receiver.span(),
+            "A function with self cannot be sent.",
+        ));
+    }
+
+    let mut sig = item.sig.clone();
+    sig.constness = None;
+    sig.ident = fn_ident.unwrap_or(Ident::new("call", Span::call_site()));
+
+    let ident = &item.sig.ident;
+    let call = forward::forwarding_call(&mut sig, &quote! {#ident});
+
+    let name = name.map(|name| quote! {name = #name,});
+    let index = index.map(|index| quote! {index = #index,});
+    let id = id.map(|id| quote! {id = #id,});
+    let sent = send_internal(
+        &quote! {#name #index #id},
+        syn::parse2(quote! {
+            impl #trait_path {
+                #(#consts)*
+                #sig {
+                    #call
+                }
+            }
+        })?,
+        copy,
+    )?;
+
+    Ok(quote! {
+        #item
+        #sent
+    })
+}
+
+/// The items of a container that stands in for a named type.\
+/// Everything that can be is forwarded to the named type. Anything else, such as methods and functions that mention Self, keeps the trait's default.
+fn forward_items(item: &ItemImpl, named: &Type, trait_path: &Path) -> Vec<TokenStream> {
+    let named = quote! {<#named as #trait_path::Trait>};
+    let self_ident = Ident::new("Self", Span::call_site());
+
+    item.items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Const(item) => {
+                let ident = &item.ident;
+                let ty = &item.ty;
+                Some(quote! {const #ident: #ty = #named::#ident;})
+            }
+            ImplItem::Type(item) => {
+                let ident = &item.ident;
+                let generics = &item.generics;
+                let (_, arguments, _) = item.generics.split_for_impl();
+                let where_clause = &item.generics.where_clause;
+                Some(quote! {type #ident #generics = #named::#ident #arguments #where_clause;})
+            }
+            ImplItem::Fn(item) => {
+                let mut sig = item.sig.clone();
+                if sig.receiver().is_some() || iterate::mentions(quote! {#sig}, &self_ident) {
+                    return None;
+                }
+
+                let ident = sig.ident.clone();
+                let call = forward::forwarding_call(&mut sig, &quote! {#named::#ident});
+
+                Some(quote! {
+                    #sig {
+                        #call
+                    }
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Sends a copy of the impl to another retrieval trait, which gets its own index.\
+/// If given a projection, then only the items with those idents are sent. The target's own options, such as its id, are sent with it.
+fn send_also(item: &ItemImpl, target: AlsoTarget, copy: usize) -> syn::Result<TokenStream> {
+    let AlsoTarget {
+        path: target,
+        options,
+        projection,
+    } = target;
+    let mut item = item.clone();
+
+    if let Some(projection) = projection {
+        let item_ident = |item: &ImplItem| match item {
+            ImplItem::Const(item) => Some(item.ident.clone()),
+            ImplItem::Fn(item) => Some(item.sig.ident.clone()),
+            ImplItem::Type(item) => Some(item.ident.clone()),
+            _ => None,
+        };
+        if let Some(missing) = projection.iter().find(|ident| {
+            !item
+                .items
+                .iter()
+                .any(|item| item_ident(item).as_ref() == Some(*ident))
+        }) {
+            return Err(syn::Error::new(
+                missing.span(),
+                "This item is not in the impl, so it cannot be sent.",
+            ));
+        }
+        item.items
+            .retain(|item| item_ident(item).is_some_and(|ident| projection.contains(&ident)));
+    }
+
+    match &mut item.trait_ {
+        Some((_, trait_path, _)) => *trait_path = target,
+        None => {
+            *item.self_ty = Type::Path(syn::TypePath {
+                qself: None,
+                path: target,
+            });
+        }
+    }
+
+    send_internal(&options, item, copy)
+}
+
+/// Finds the retrieval trait's path, and the named type if one is being sent.\
+/// A trait impl sends a named type, while an inherent impl sends an anonymous container.
+fn sent_trait(item: &mut ItemImpl) -> syn::Result<(Path, Option<Type>)> {
+    match item.trait_.take() {
+        Some((None, trait_path, _)) => {
+            if !item.generics.params.is_empty() {
+                return Err(syn::Error::new(
+                    item.generics.span(),
+                    "A named type cannot be generic when sent for retrieval.",
+                ));
+            }
+            Ok((trait_path, Some((*item.self_ty).clone())))
+        }
+        Some((Some(bang), ..)) => Err(syn::Error::new(
+            bang.span(),
+            "A negative impl cannot be sent for retrieval.",
+        )),
+        None => {
+            let Type::Path(trait_path) = &*item.self_ty else {
+                return Err(syn::Error::new(
+                    item.self_ty.span(),
+                    "The inherent impl's type is not a retrieval trait, which doesn't make sense.",
+                ));
+            };
+            Ok((trait_path.path.clone(), None))
+        }
+    }
+}
+
+/// Counts the implementation under the id, so that it gets the next index, and replaces the previous final implementation.\
+/// The id defaults to the trait's name as written. It is checked against the trait's own id, so that a trait sent through a different name fails to compile.
+fn counted_implementation(
+    internal_path: &Path,
+    trait_path: &Path,
+    id: Option<LitStr>,
+) -> syn::Result<(u16, TokenStream)> {
+    let ident = last_ident(trait_path)?;
+    let id = id.unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+    let message = format!(
+        "This implementation was counted under `{}`, which is not the retrieval trait's id. Send it through the trait's own name, or give it the trait's id, using id = \"...\".",
+        id.value(),
+    );
+    let check = quote_spanned! {id.span()=>
+        const _: () = ::core::assert!(#internal_path::is_id(#id), #message);
+    };
+
+    let shared = shared::enabled();
+    let shared_message = "This implementation and its retrieval trait disagree on whether the crate's switches are shared. Invoke retrieval::shared_switches! at the top of the crate root, before any retrieval trait or send.";
+    let shared_check = quote! {
+        const _: () = ::core::assert!(#internal_path::SHARED == #shared, #shared_message);
+    };
+    if let Some((previous_implementations, implementation)) =
+        shared::next_implementation(internal_path, &id.value())?
+    {
+        capacity_check(&id, ident, trait_path, previous_implementations)?;
+        return Ok((
+            previous_implementations,
+            quote! {
+                #implementation
+                #check
+                #shared_check
+            },
+        ));
+    }
+
+    let (previous_implementations, replace) =
+        replaceable_implementations::replace_implementation(internal_path, id.value(), true)
+            .map_err(|err| match err {
+                VarError::NotPresent => syn::Error::new(
+                    Span::call_site(),
+                    "The crate name was not present in the environment variables.",
+                ),
+                VarError::NotUnicode(crate_name) => syn::Error::new(
+                    Span::call_site(),
+                    format!(
+                        "The crate name was not unicode. Crate name: {}",
+                        crate_name.display(),
+                    ),
+                ),
+            })?;
+    capacity_check(&id, ident, trait_path, previous_implementations)?;
+    let implementation = replace(syn::parse2(quote! {
+        impl #internal_path::Final for #internal_path::Container<#previous_implementations> {}
+    })?);
+
+    Ok((
+        previous_implementations,
+        quote! {
+            #implementation
+            #check
+            #shared_check
+        },
+    ))
+}
+
+/// Fails if the implementation is past the capacity its retrieval trait claimed, if it has been expanded yet.\
+/// Caught here when possible, as the missing switch would otherwise be the only error.
+fn capacity_check(
+    id: &LitStr,
+    ident: &Ident,
+    trait_path: &Path,
+    previous_implementations: u16,
+) -> syn::Result<()> {
+    if let Some(capacity) = claimed_capacity(&id.value())
+        && u32::from(previous_implementations) > capacity
+    {
+        return Err(syn::Error::new(
+            trait_path.span(),
+            format!(
+                "The retrieval trait `{ident}` was declared with capacity = {capacity}, but this is implementation {previous_implementations}. Raise its capacity, such as with #[retrieve(capacity = {})].",
+                (capacity * 2).max(previous_implementations.into()),
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn send_internal(input: &TokenStream, mut item: ItemImpl, copy: usize) -> syn::Result<TokenStream> {
+    let SendAttribute {
+        name,
+        index,
+        id,
+        trait_path,
+        fn_ident,
+        consts,
+        also,
+    } = syn::parse2(input.clone())?;
+    if trait_path.is_some() || fn_ident.is_some() || !consts.is_empty() {
+        return Err(syn::Error::new(
+            input.span(),
+            "Only a sent function can be given a retrieval trait, fn, or consts.",
+        ));
+    }
+    let also = also
+        .into_iter()
+        .map(|target| send_also(&item, target, copy))
+        .collect::<syn::Result<TokenStream>>()?;
+
+    let (trait_path, named) = sent_trait(&mut item)?;
+    let internal_path = internal_path(&trait_path)?;
+    let (previous_implementations, implementation) = match index.map_or_else(
+        || registry::index(&trait_path, copy),
+        |index| Ok(Some(index)),
+    )? {
+        Some(index) => (index, explicit::implementation(&internal_path, index)),
+        None => counted_implementation(&internal_path, &trait_path, id)?,
+    };
+
+    let current = LitInt::new(&(previous_implementations).to_string(), Span::call_site());
+    let next = LitInt::new(
+        &(previous_implementations - 1).to_string(),
+        Span::call_site(),
+    );
+    let links = quote! {
+        type NEXT = #trait_path::__internal::Container<#next>;
+        const INDEX: u16 = #current;
+    };
+
+    let container = if let Some(named) = &named {
+        let forwarded = forward_items(&item, named, &trait_path);
+        // A private type can't be the ITEM of a public container, so the error should point at the type.
+        let item_type = quote_spanned! {named.span()=>
+            type ITEM = #named;
+        };
+        quote! {
+            impl #trait_path::Trait for #trait_path::__internal::Container<#current> {
+                #(#forwarded)*
+                #links
+                #item_type
+            }
+        }
+    } else {
+        *item.self_ty = Type::Verbatim(quote! {#trait_path::__internal::Container<#current>});
+        TokenStream::new()
+    };
+
+    item.trait_ = Some((
+        None,
+        syn::parse2(quote! {#trait_path::Trait})?,
+        <Token![for]>::default(),
+    ));
+
+    item.items.push(ImplItem::Verbatim(quote! {
+        #links
+        type ITEM = Self;
+    }));
+
+    let kind = name.map(|name| {
+        let doc = format!("The Kind of the implementation named {name}.");
+        quote! {
+            impl #trait_path::Kind {
+                #[doc = #doc]
+                #[allow(non_upper_case_globals)]
+                pub const #name: #trait_path::Kind = #trait_path::Kind::ALL[#current as usize - 1];
+            }
+        }
+    });
+
+    let message = format!(
+        "This is implementation {current} of `{}`, which is more than the capacity it was declared with. Raise its capacity using #[retrieve(capacity = ...)].",
+        last_ident(&trait_path)?,
+    );
+    let capacity = quote_spanned! {trait_path.span()=>
+        const _: () = ::core::assert!(#current <= #trait_path::__internal::CAPACITY, #message);
+    };
+
+    let output = quote! {
+        #item
+        #container
+        #implementation
+        #capacity
+        #kind
+        #also
+    };
+
+    Ok(output)
+}
+
+/// Place on a generic function to remove its retrieval generic, turning it into a function that iterates through all implementations of a [retrieval trait](macro@retrieve).\
+/// Optionally allows the capacity to be specified. Defaults to the retrieval trait's capacity. The higher the capacity, the longer it will take to compile.\
+/// Without one, the trait's module expands the function again with its capacity, through the name `iterate`, so it must be in scope under that name.\
+/// A capacity below the trait's quantity is a compile error, as some implementations would never be visited.
+///
+/// Implementations are visited from the last sent to the first, unless `order = forward` is specified.
+///
+/// The first type param must be bound by the retrieval trait. The retrieval trait is the only bound that isn't a common standard library trait, and may be in the where clause.\
+/// If more than one bound could be the retrieval trait, choose it with `trait = Path`.\
+/// Any other generics are kept by the resulting function, and passed along unchanged.
+///
+/// Inputs can be any pattern. Each implementation is given the inputs in turn, along with any changes the ones before it made to them.\
+/// As they are passed on after the block, the block can't move out of an input, other than the accumulator.
+///
+/// The retrieval generic can have other bounds and where clauses, which are checked against every implementation.\
+/// This requires calling every implementation individually, so each one up to the capacity gets its own call, making the capacity more expensive than usual.\
+/// As each call is given the same inputs, any taken by value must be Copy, other than the accumulator.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve]
+/// trait Message {
+///     const STR: &str = "";
+/// }
+///
+/// #[iterate]
+/// const fn collect_messages<T: Message>(messages: &mut [&str], index: &mut usize) {
+///     messages[*index] = T::STR;
+///     *index += 1;
+/// }
+///
+/// const MESSAGES: [&str; Message::QUANTITY as usize] = {
+///     let mut messages = [""; Message::QUANTITY as usize];
+///     let mut index = 0;
+///
+///     collect_messages(&mut messages, &mut index);
+///     messages
+/// };
+/// ```
+///
+/// Implementations can be visited in the order they were sent.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// # #[retrieve]
+/// # trait Message {
+/// #     const STR: &str = "";
+/// # }
+/// #[iterate(capacity = 1000, order = forward)]
+/// fn print_in_order<T: Message>() {
+///     println!("{}", T::STR);
+/// }
+/// ```
+///
+/// An input marked with `#[position]` is given the zero-based position of the current implementation in the iteration, instead of being passed in.\
+/// The retrieval generic's INDEX is also available.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// # #[retrieve]
+/// # trait Message {
+/// #     const STR: &str = "";
+/// # }
+/// #[iterate]
+/// const fn collect_messages<T: Message>(messages: &mut [&str], #[position] position: usize) {
+///     messages[position] = T::STR;
+/// }
+///
+/// const MESSAGES: [&str; Message::QUANTITY as usize] = {
+///     let mut messages = [""; Message::QUANTITY as usize];
+///     collect_messages(&mut messages);
+///     messages
+/// };
+/// ```
+///
+/// Other generics can be used alongside the retrieval generic.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// # #[retrieve]
+/// # trait Message {
+/// #     const STR: &str = "";
+/// # }
+/// #[iterate]
+/// fn dump<T: Message, W: core::fmt::Write>(out: &mut W) {
+///     let _ = writeln!(out, "{}", T::STR);
+/// }
+///
+/// fn dump_to_string() -> String {
+///     let mut out = String::new();
+///     dump(&mut out);
+///     out
+/// }
+/// ```
+///
+/// Extra bounds on the retrieval generic must hold for every implementation.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve(10)]
+/// trait Plugin {
+///     type Config = ();
+/// }
+///
+/// #[iterate(10)]
+/// fn default_configs<T: Plugin + ?Sized>(configs: &mut Vec<String>)
+/// where
+///     T::Config: Default + core::fmt::Debug,
+/// {
+///     configs.push(format!("{:?}", T::Config::default()));
+/// }
+/// ```
+///
+/// Works on associated functions and methods inside impl blocks too.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// struct Engine {
+///     ticks: u32,
+/// }
+///
+/// #[retrieve]
+/// trait System {
+///     fn run(engine: &mut Engine) {}
+/// }
+///
+/// impl Engine {
+///     #[iterate]
+///     fn run_systems<S: System>(&mut self) {
+///         S::run(self);
+///     }
+/// }
+/// ```
+///
+/// If the function returns a value, then it folds over every implementation.\
+/// The first input is the accumulator. It is passed into the first implementation, and whatever that returns is passed into the next, and so on.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve]
+/// trait Cost {
+///     const COST: u32 = 0;
+/// }
+///
+/// #[iterate]
+/// const fn total<T: Cost>(acc: u32) -> u32 {
+///     acc + T::COST
+/// }
+///
+/// const TOTAL: u32 = total(0);
+/// ```
+///
+/// If the function returns a [`ControlFlow`](core::ops::ControlFlow), then iteration stops at the first Break.\
+/// The resulting function returns the Break's value as Some, or None if every implementation continued.
+/// ```rust
+/// # use retrieval::*;
+/// # use core::ops::ControlFlow;
+/// # fn main() {}
+/// #[retrieve]
+/// trait Message {
+///     const STR: &str = "";
+/// }
+///
+/// #[iterate]
+/// const fn find_long<T: Message>(minimum: usize) -> ControlFlow<&'static str> {
+///     if T::STR.len() >= minimum {
+///         ControlFlow::Break(T::STR)
+///     } else {
+///         ControlFlow::Continue(())
+///     }
+/// }
+///
+/// const FOUND: Option<&str> = find_long(10);
+/// ```
+#[proc_macro_attribute]
+pub fn iterate(input: StdTokenStream, item: StdTokenStream) -> StdTokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+    iterate::iterate_internal(input.into(), item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Runs the closure's body once for every implementation of a [retrieval trait](macro@retrieve), in expression position.\
+/// The closure's input is the ident given to each implementation. Unlike an [iterate](macro@iterate) function, the body can use any local variables.
+///
+/// Optionally allows the capacity and order to be specified between the trait and the closure, just like [iterate](macro@iterate).\
+/// The body is copied once per index up to the capacity, so a smaller capacity compiles much faster. Defaults to the retrieval trait's capacity, in the same way.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// #[retrieve(10)]
+/// trait Message {
+///     const STR: &str = "";
+/// }
+///
+/// fn print_messages(prefix: &str) {
+///     for_each!(Message, 10, |T| {
+///         println!("{prefix}{}", T::STR);
+///     });
+/// }
+///
+/// const MESSAGE_LENGTH: usize = {
+///     let mut length = 0;
+///     for_each!(Message, capacity = 10, order = forward, |T| length += T::STR.len());
+///     length
+/// };
+/// ```
+#[proc_macro]
+pub fn for_each(input: StdTokenStream) -> StdTokenStream {
+    for_each::for_each_internal(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Declares one pool of switches for every counted [retrieval trait](macro@retrieve) in the crate, instead of each trait generating a capacity's worth of its own.\
+/// Every implementation takes a single switch from the pool, so adding a retrieval trait only costs as many switches as it has implementations, plus one.
+///
+/// Optionally allows the pool's capacity to be specified. Defaults to 1000.\
+/// Must be invoked in the crate root, before any retrieval trait or send, as they only share switches if the pool exists when they expand.
+/// Traits with `explicit`, or scanned by [retrieval-build](https://docs.rs/retrieval-build/), already only have the switches they need, and are left alone.
+/// ```rust
+/// # use retrieval::*;
+/// # fn main() {}
+/// shared_switches!(100);
+///
+/// #[retrieve]
+/// trait Message {
+///     const STR: &str = "";
+/// }
+///
+/// #[retrieve]
+/// trait Cost {
+///     const COST: u32 = 0;
+/// }
+///
+/// #[send]
+/// impl Message {
+///     const STR: &str = "Hello world!";
+/// }
+///
+/// #[send]
+/// impl Cost {
+///     const COST: u32 = 5;
+/// }
+/// ```
+#[proc_macro]
+pub fn shared_switches(input: StdTokenStream) -> StdTokenStream {
+    let capacity = if input.is_empty() {
+        Ok(1000)
+    } else {
+        syn::parse::<LitInt>(input).and_then(|capacity| capacity.base10_parse())
+    };
+    capacity
+        .map_or_else(syn::Error::into_compile_error, shared::setup)
+        .into()
+}
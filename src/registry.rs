@@ -0,0 +1,61 @@
+//! Reads the indices assigned by retrieval-build, when the crate's build script generated them.
+
+use proc_macro2::Span;
+use retrieval_build::Registry;
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+use syn::Path;
+
+/// A registry's path, and when it was last modified.
+type Key = (PathBuf, Option<SystemTime>);
+
+/// Every registry read so far, so that each is only parsed once.
+static LOADED: Mutex<BTreeMap<Key, Arc<Registry>>> = Mutex::new(BTreeMap::new());
+
+/// The file and line of the macro's attribute, which identify it in the registry.
+fn call_site() -> Option<(PathBuf, usize)> {
+    let span = proc_macro::Span::call_site();
+    Some((span.local_file()?.canonicalize().ok()?, span.line()))
+}
+
+fn load() -> syn::Result<Option<Arc<Registry>>> {
+    let Some(path) = std::env::var_os(retrieval_build::ENV) else {
+        return Ok(None);
+    };
+    let path = PathBuf::from(path);
+    let modified = std::fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    let mut loaded = LOADED.lock().unwrap_or_else(PoisonError::into_inner);
+    let key = (path, modified);
+    if let Some(registry) = loaded.get(&key) {
+        return Ok(Some(registry.clone()));
+    }
+    let registry = Arc::new(
+        Registry::read(&key.0).map_err(|error| syn::Error::new(Span::call_site(), error))?,
+    );
+    loaded.insert(key, registry.clone());
+    Ok(Some(registry))
+}
+
+/// The quantity of the retrieval trait being retrieved, if the build script scanned it.
+pub(crate) fn quantity() -> syn::Result<Option<u16>> {
+    let Some(registry) = load()? else {
+        return Ok(None);
+    };
+    Ok(call_site().and_then(|(file, line)| registry.quantity(&file, line)))
+}
+
+/// The index the build script assigned to this copy of the send, for the retrieval trait.
+pub(crate) fn index(trait_path: &Path, copy: usize) -> syn::Result<Option<u16>> {
+    let Some(registry) = load()? else {
+        return Ok(None);
+    };
+    let name = crate::last_ident(trait_path)?.to_string();
+    Ok(call_site().and_then(|(file, line)| registry.index(&file, line, copy, &name)))
+}
@@ -0,0 +1,136 @@
+//! The implementation of shared switches, where every counted retrieval trait in a crate chains its implementations through one pool of switches.\
+//! A switch can only ever be turned off for every type at once, so each one is handed to a single implementation.\
+//! The pool then only needs as many switches as the crate has implementations, rather than a capacity's worth for every trait.
+
+use crate::switches;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, PoisonError},
+};
+use syn::Path;
+
+/// The switches handed out in a crate.
+struct Pool {
+    capacity: u32,
+    used: u32,
+    /// The switch of every implementation of every retrieval trait, by id, starting from the end of the chain.
+    chains: BTreeMap<String, Vec<u32>>,
+}
+
+/// The pool of every crate that declared one.
+static POOLS: Mutex<BTreeMap<String, Pool>> = Mutex::new(BTreeMap::new());
+
+/// The prefix of the switches in the pool.
+const PREFIX: &str = "Shared";
+
+/// Where the pool is placed. It must be declared in the crate root.
+fn pool_path() -> TokenStream {
+    quote! {crate::__retrieval_shared}
+}
+
+/// Does the crate share its switches?
+pub(crate) fn enabled() -> bool {
+    POOLS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains_key(&crate::crate_name())
+}
+
+/// Generates the pool of switches, and starts handing them out.
+pub(crate) fn setup(capacity: u32) -> TokenStream {
+    POOLS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(crate::crate_name())
+        .or_insert_with(|| Pool {
+            capacity,
+            used: 0,
+            chains: BTreeMap::new(),
+        })
+        .capacity = capacity;
+
+    let switches = switches::setup(PREFIX, capacity);
+    quote! {
+        #[doc(hidden)]
+        /// !!! INTERNAL !!!
+        /// The switches shared by every retrieval trait in the crate.
+        pub mod __retrieval_shared {
+            #switches
+        }
+    }
+}
+
+/// Hands out the switch for an index in the retrieval trait's chain, and any before it that haven't been yet.\
+/// Returns the switches of the index before it and the index, or None if the crate doesn't share its switches. Fails if the pool has run out.
+fn hand_out(id: &str, index: u16) -> syn::Result<Option<(Option<u32>, u32)>> {
+    let mut pools = POOLS.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(pool) = pools.get_mut(&crate::crate_name()) else {
+        return Ok(None);
+    };
+
+    let chain = pool.chains.entry(id.to_string()).or_default();
+    while chain.len() <= usize::from(index) {
+        if pool.used >= pool.capacity {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                format!(
+                    "The crate's {} shared switches have all been used. Raise the capacity, such as with retrieval::shared_switches!({}).",
+                    pool.capacity,
+                    (pool.capacity * 2).max(1),
+                ),
+            ));
+        }
+        chain.push(pool.used);
+        pool.used += 1;
+    }
+
+    let index = usize::from(index);
+    Ok(Some((
+        index.checked_sub(1).map(|previous| chain[previous]),
+        chain[index],
+    )))
+}
+
+/// Marks the index as final, and unmarks the index before it.\
+/// Returns None if the crate doesn't share its switches.
+pub(crate) fn implementation(
+    internal_path: &Path,
+    id: &str,
+    index: u16,
+) -> syn::Result<Option<TokenStream>> {
+    let Some(switches) = hand_out(id, index)? else {
+        return Ok(None);
+    };
+    Ok(Some(switches::implementation(
+        PREFIX,
+        &pool_path(),
+        internal_path,
+        switches,
+        index,
+    )))
+}
+
+/// Hands out the next index in the retrieval trait's chain, along with its implementation.\
+/// Returns None if the crate doesn't share its switches.
+pub(crate) fn next_implementation(
+    internal_path: &Path,
+    id: &str,
+) -> syn::Result<Option<(u16, TokenStream)>> {
+    let index = {
+        let pools = POOLS.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(pool) = pools.get(&crate::crate_name()) else {
+            return Ok(None);
+        };
+        // The end of the chain is index 0, even if the retrieval trait hasn't taken its switch yet.
+        let len = pool.chains.get(id).map_or(1, Vec::len).max(1);
+        u16::try_from(len).map_err(|_| {
+            syn::Error::new(
+                Span::call_site(),
+                "The retrieval trait was sent more implementations than a u16 can index.",
+            )
+        })?
+    };
+    Ok(implementation(internal_path, id, index)?.map(|implementation| (index, implementation)))
+}
@@ -0,0 +1,58 @@
+//! The switches that chain implementations with known indices, used by explicit mode and by shared switches.\
+//! Every switch is on until the implementation after it turns it off, so only the last implementation is final.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Path};
+
+/// The switch for an index.\
+/// Each user of the switches names them with its own prefix, so that their sends can't be mixed with each other, or with counted sends.
+fn switch_ident(prefix: &str, index: u32) -> Ident {
+    quote::format_ident!("{prefix}Switch{index}")
+}
+
+/// Generates the switches, along with the Is trait that every implementation is bounded by.
+pub(crate) fn setup(prefix: &str, quantity: u32) -> TokenStream {
+    let switches = (0..quantity).map(|index| {
+        let ident = switch_ident(prefix, index);
+        quote! {
+            pub struct #ident<T, const BOOL: bool>(::core::marker::PhantomData<T>);
+        }
+    });
+
+    quote! {
+        /// Self is the same type as T.
+        /// Used to bypass trivial bounds.
+        pub trait Is<T> {}
+        impl<T> Is<T> for T {}
+
+        #(#switches)*
+    }
+}
+
+/// Marks the index as final using its switch, and turns off the previous implementation's switch.\
+/// The switches are found at the given path, and the container at the internal path.
+pub(crate) fn implementation(
+    prefix: &str,
+    path: &TokenStream,
+    internal_path: &Path,
+    (previous, switch): (Option<u32>, u32),
+    index: u16,
+) -> TokenStream {
+    let switch = switch_ident(prefix, switch);
+    let previous = previous.map(|previous| {
+        let previous = switch_ident(prefix, previous);
+        quote! {
+            impl<T> ::core::marker::Unpin for #path::#previous<T, false> {}
+        }
+    });
+
+    quote! {
+        #previous
+        impl<T: #path::Is<#internal_path::Container<#index>>> #internal_path::Final for T
+        where
+            #path::#switch<T, true>: ::core::marker::Unpin,
+        {
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! The implementation of value registries, which retrieve plain values instead of implementations.
+
+use crate::RetrieveAttribute;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident, ItemConst, Path, Type, Visibility, spanned::Spanned};
+
+/// The hidden retrieval trait's ident, for a type's ident.
+fn registry_ident(ident: &Ident) -> Ident {
+    Ident::new(&format!("__retrieval_{ident}"), ident.span())
+}
+
+/// Creates a hidden retrieval trait next to the type, whose implementations each hold a value.\
+/// The type gets the QUANTITY of values, and ALL of them.
+pub(crate) fn retrieve_value_internal(
+    input: TokenStream,
+    ident: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+) -> syn::Result<TokenStream> {
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new(
+            generics.span(),
+            "A type cannot be generic when retrieving its values.",
+        ));
+    }
+    let (_, capacity) =
+        crate::scanned_capacity(syn::parse2::<RetrieveAttribute>(input.clone())?.capacity)?;
+    let registry = registry_ident(ident);
+
+    let registry_trait = crate::retrieve_internal(
+        input,
+        syn::parse2(quote! {
+            #vis trait #registry {
+                const VALUE: ::core::option::Option<#ident> = ::core::option::Option::None;
+            }
+        })?,
+    )?;
+
+    let collect = crate::iterate::iterate_internal(
+        quote! {#capacity, order = forward},
+        syn::parse2(quote! {
+            const fn collect<T: #registry>(
+                all: &mut [::core::mem::MaybeUninit<#ident>],
+                #[position] position: usize,
+            ) {
+                if let ::core::option::Option::Some(value) = T::VALUE {
+                    all[position] = ::core::mem::MaybeUninit::new(value);
+                }
+            }
+        })?,
+    )?;
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #registry_trait
+
+        impl #ident {
+            /// The amount of values sent.
+            pub const QUANTITY: u16 = #registry::QUANTITY;
+
+            /// Every value, in the order they were sent.
+            pub const ALL: [#ident; #registry::QUANTITY as usize] = {
+                #collect
+
+                let mut all = [const { ::core::mem::MaybeUninit::uninit() }; #registry::QUANTITY as usize];
+                collect(&mut all);
+                // SAFETY: Every implementation up to the QUANTITY was sent with a value, and collect writes each one.
+                unsafe { ::core::mem::transmute(all) }
+            };
+        }
+    })
+}
+
+/// Sends the const's value to the hidden retrieval trait of its type.\
+/// A named const is kept, while an unnamed const only exists as a value.
+pub(crate) fn send_value_internal(
+    input: &TokenStream,
+    item: &ItemConst,
+    copy: usize,
+) -> syn::Result<TokenStream> {
+    let Type::Path(path) = &*item.ty else {
+        return Err(syn::Error::new(
+            item.ty.span(),
+            "Expected the const's type to be a type whose values are retrieved.",
+        ));
+    };
+    let mut registry: Path = path.path.clone();
+    let segment = registry.segments.last_mut().ok_or(syn::Error::new(
+        path.span(),
+        "Expected the type's path to contain at least one segment.",
+    ))?;
+    segment.ident = registry_ident(&segment.ident);
+
+    let ty = &item.ty;
+    let (kept, value) = if item.ident == "_" {
+        let expr = &item.expr;
+        (TokenStream::new(), quote! {#expr})
+    } else {
+        let ident = &item.ident;
+        (quote! {#item}, quote! {#ident})
+    };
+
+    let sent = crate::send_internal(
+        input,
+        syn::parse2(quote! {
+            impl #registry {
+                const VALUE: ::core::option::Option<#ty> = ::core::option::Option::Some(#value);
+            }
+        })?,
+        copy,
+    )?;
+
+    Ok(quote! {
+        #kept
+        #sent
+    })
+}
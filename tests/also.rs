@@ -0,0 +1,95 @@
+//! Tests sending a single impl to several retrieval traits.
+
+use retrieval::*;
+
+#[retrieve(capacity = 10, collect_consts, collect_fns)]
+trait Command {
+    const NAME: &str = "";
+    fn run() -> u8 {
+        0
+    }
+}
+
+#[retrieve(capacity = 10, collect_consts)]
+trait HelpTopic {
+    const NAME: &str = "";
+    const HELP: &str = "";
+}
+
+#[retrieve(capacity = 10, collect_consts)]
+trait Named {
+    const NAME: &str = "";
+}
+
+#[send(also = [Named { NAME }])]
+impl HelpTopic {
+    const NAME: &str = "introduction";
+    const HELP: &str = "Welcome!";
+}
+
+#[send(also = [HelpTopic { NAME }, Named { NAME }])]
+impl Command {
+    const NAME: &str = "quit";
+    fn run() -> u8 {
+        1
+    }
+}
+
+pub struct Echo;
+
+#[send(also = [HelpTopic { NAME }])]
+impl Command for Echo {
+    const NAME: &str = "echo";
+}
+
+#[send(also = [Named])]
+impl Command {
+    const NAME: &str = "unlisted";
+}
+
+mod topics {
+    use retrieval::*;
+
+    #[retrieve(id = "topics", capacity = 10, collect_consts)]
+    pub trait Topic {
+        const NAME: &str = "";
+    }
+}
+use topics::Topic;
+
+#[retrieve(capacity = 10, explicit, collect_consts)]
+trait Ordered {
+    const NAME: &str = "";
+}
+
+#[retrieve(capacity = 10)]
+trait Setting {
+    const NAME: &str = "";
+}
+
+// Each target gets its own options.
+#[send(also = [Topic(id = "topics"), Ordered(index = 2) { NAME }])]
+impl Setting {
+    const NAME: &str = "volume";
+}
+
+#[send(index = 1)]
+impl Ordered {
+    const NAME: &str = "first";
+}
+
+const _: () = assert!(Command::QUANTITY == 3);
+const _: () = assert!(HelpTopic::QUANTITY == 3);
+const _: () = assert!(Named::QUANTITY == 3);
+
+#[test]
+fn main() {
+    assert_eq!(Command::ALL_NAME, ["quit", "echo", "unlisted"]);
+    let runs: Vec<u8> = Command::RUN.iter().map(|run| run()).collect();
+    assert_eq!(runs, [1, 0, 0]);
+    assert_eq!(HelpTopic::ALL_NAME, ["introduction", "quit", "echo"]);
+    assert_eq!(HelpTopic::ALL_HELP, ["Welcome!", "", ""]);
+    assert_eq!(Named::ALL_NAME, ["introduction", "quit", "unlisted"]);
+    assert_eq!(Topic::ALL_NAME, ["volume"]);
+    assert_eq!(Ordered::ALL_NAME, ["first", "volume"]);
+}
@@ -0,0 +1,78 @@
+//! Tests that the retrieval generic can have extra bounds and where clauses.
+
+use core::fmt::Debug;
+use retrieval::*;
+
+#[retrieve(4)]
+trait Plugin {
+    type Config = ();
+    const NAME: &str = "";
+}
+
+#[iterate(4)]
+fn names<T: Plugin + ?Sized>(names: &mut Vec<&'static str>) {
+    names.push(T::NAME);
+}
+
+#[iterate(4)]
+fn configs<T>(configs: &mut Vec<String>)
+where
+    T: Plugin,
+    T::Config: Default + Debug,
+{
+    configs.push(format!("{:?}", T::Config::default()));
+}
+
+#[iterate(4)]
+const fn sized<T: Sized + Plugin>(acc: usize) -> usize
+where
+    T::Config: Copy,
+{
+    acc + size_of::<T::Config>()
+}
+
+const _: () = assert!(sized(0) == 5);
+
+trait Marker {}
+impl<T: ?Sized> Marker for T {}
+
+#[iterate(4, trait = Plugin)]
+fn marked<T: Marker + Plugin>(names: &mut Vec<&'static str>) {
+    names.push(T::NAME);
+}
+
+#[iterate(order = forward, trait = Plugin)]
+fn marked_forward<T>(names: &mut Vec<&'static str>)
+where
+    T: Marker + Plugin,
+{
+    names.push(T::NAME);
+}
+
+#[test]
+fn main() {
+    let mut names_found = vec![];
+    names(&mut names_found);
+    assert_eq!(names_found, ["second", "first"]);
+
+    let mut configs_found = vec![];
+    configs(&mut configs_found);
+    assert_eq!(configs_found, ["0", "false"]);
+
+    let mut marked_found = vec![];
+    marked(&mut marked_found);
+    marked_forward(&mut marked_found);
+    assert_eq!(marked_found, ["second", "first", "first", "second"]);
+}
+
+#[send]
+impl Plugin {
+    type Config = bool;
+    const NAME: &str = "first";
+}
+
+#[send]
+impl Plugin {
+    type Config = u32;
+    const NAME: &str = "second";
+}
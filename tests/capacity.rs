@@ -1,4 +1,4 @@
-//! Tests that 2 implementations can fit in a capacity of 2.
+//! Tests that 2 implementations can fit in a capacity of 2, which iterate and for_each pick up from the trait.
 
 use retrieval::*;
 
@@ -7,13 +7,20 @@ trait Number {
     const NUMBER: u8 = 0;
 }
 
-#[iterate(2)]
+#[iterate]
 const fn testing<T: Number>() {
     assert!((T::NUMBER == 5) || (T::NUMBER == 112));
 }
 
 const _: () = testing();
 
+const SUM: u8 = {
+    let mut sum = 0;
+    for_each!(Number, order = forward, |T| sum += T::NUMBER);
+    sum
+};
+const _: () = assert!(SUM == 117);
+
 #[send]
 impl Number {
     const NUMBER: u8 = 5;
@@ -0,0 +1,56 @@
+//! Tests that retrieval traits can collect every associated const into an array.
+
+use retrieval::*;
+
+#[retrieve(capacity = 10, collect_consts)]
+trait Message {
+    const STR: &str = "";
+    const PRIORITY: u8 = 0;
+    const OWNED: Vec<u8> = Vec::new();
+    type Payload = ();
+    const PAYLOAD: Option<Self::Payload> = None;
+}
+
+#[retrieve(collect_consts, 10)]
+trait Empty {
+    const NOTHING: () = ();
+}
+
+const _: () = assert!(Message::ALL_STR.len() == 3);
+const _: () = assert!(Message::ALL_PRIORITY[0] == 1 && Message::ALL_PRIORITY[2] == 3);
+const _: () = assert!(Empty::ALL_NOTHING.is_empty());
+
+#[test]
+fn main() {
+    assert_eq!(Message::ALL_STR, ["first", "second", "third"]);
+    assert_eq!(Message::ALL_PRIORITY, [1, 2, 3]);
+    assert_eq!(Message::ALL_OWNED, [vec![], vec![], vec![]]);
+
+    // Consts that mention Self aren't collected, as their type differs between implementations.
+    let mut payloads = 0;
+    for_each!(Message, 10, |T| payloads +=
+        usize::from(T::PAYLOAD.is_some()));
+    assert_eq!(payloads, 1);
+}
+
+#[send]
+impl Message {
+    const STR: &str = "first";
+    const PRIORITY: u8 = 1;
+    type Payload = u8;
+    const PAYLOAD: Option<u8> = Some(5);
+}
+
+#[send]
+impl Message {
+    const STR: &str = "second";
+    const PRIORITY: u8 = 2;
+    type Payload = ();
+}
+
+#[send]
+impl Message {
+    const STR: &str = "third";
+    const PRIORITY: u8 = 3;
+    type Payload = ();
+}
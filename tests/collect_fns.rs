@@ -0,0 +1,79 @@
+//! Tests that retrieval traits can collect every associated function into an array of function pointers.
+
+use retrieval::*;
+
+#[retrieve(capacity = 10, collect_fns)]
+trait Handler {
+    fn handle(input: u32) -> u32 {
+        input
+    }
+    fn name() -> &'static str {
+        ""
+    }
+    fn borrow(input: &str) -> &str {
+        input
+    }
+    unsafe fn dangerous() {}
+    fn generic<T>() {}
+    #[allow(dead_code)]
+    fn uses_self(&self) {}
+    fn mentions_self() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+#[retrieve(collect_fns, collect_consts, 10)]
+trait Empty {
+    fn nothing() {}
+}
+
+const _: () = assert!(Handler::HANDLE.len() == 3);
+const _: () = assert!(Empty::NOTHING.is_empty());
+
+#[test]
+fn main() {
+    let results: Vec<u32> = Handler::HANDLE.iter().map(|handle| handle(10)).collect();
+    assert_eq!(results, [11, 20, 10]);
+
+    let names: Vec<&str> = Handler::NAME.iter().map(|name| name()).collect();
+    assert_eq!(names, ["add", "double", "default"]);
+
+    assert_eq!(Handler::BORROW[1]("borrowed"), "borrowed");
+    unsafe { Handler::DANGEROUS[0]() };
+
+    // Methods, generics, and anything mentioning Self can't be function pointers, so they are only reachable through the trait.
+    for_each!(Handler, 10, |T| {
+        T::generic::<u8>();
+        assert!(T::mentions_self().is_none());
+    });
+}
+
+#[send]
+impl Handler {
+    fn handle(input: u32) -> u32 {
+        input + 1
+    }
+    fn name() -> &'static str {
+        "add"
+    }
+}
+
+#[send]
+impl Handler {
+    fn handle(input: u32) -> u32 {
+        input * 2
+    }
+    fn name() -> &'static str {
+        "double"
+    }
+}
+
+#[send]
+impl Handler {
+    fn name() -> &'static str {
+        "default"
+    }
+}
@@ -0,0 +1,49 @@
+//! Tests that iterate functions returning a ControlFlow stop at the first Break.
+
+use core::ops::ControlFlow;
+use retrieval::*;
+
+#[retrieve]
+trait Message {
+    const STR: &str = "";
+}
+
+#[iterate]
+const fn find_long<T: Message>(minimum: usize, visited: &mut usize) -> ControlFlow<&'static str> {
+    *visited += 1;
+    if T::STR.len() >= minimum {
+        ControlFlow::Break(T::STR)
+    } else {
+        ControlFlow::Continue(())
+    }
+}
+
+const FOUND: (Option<&str>, usize) = {
+    let mut visited = 0;
+    (find_long(3, &mut visited), visited)
+};
+const _: () = assert!(FOUND.1 == 2);
+
+#[test]
+fn main() {
+    assert_eq!(FOUND.0, Some("bbbb"));
+
+    let mut visited = 0;
+    assert_eq!(find_long(10, &mut visited), None);
+    assert_eq!(visited, 3);
+}
+
+#[send]
+impl Message {
+    const STR: &str = "a";
+}
+
+#[send]
+impl Message {
+    const STR: &str = "bbbb";
+}
+
+#[send]
+impl Message {
+    const STR: &str = "cc";
+}
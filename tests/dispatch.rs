@@ -0,0 +1,111 @@
+//! Tests visiting an implementation chosen at runtime.
+
+use retrieval::*;
+
+#[retrieve(10, dispatch)]
+trait Command {
+    const NAME: &str = "";
+    fn run(_input: &mut Vec<String>) {}
+}
+
+#[retrieve(10, dispatch)]
+trait Empty {}
+
+struct Run<'a>(&'a mut Vec<String>);
+
+impl Command::Visitor for Run<'_> {
+    type Output = &'static str;
+    fn visit<T: Command::Trait>(self) -> &'static str {
+        T::run(self.0);
+        T::NAME
+    }
+}
+
+struct Index;
+
+impl Command::Visitor for Index {
+    type Output = u16;
+    fn visit<T: Command::Trait>(self) -> u16 {
+        T::INDEX
+    }
+}
+
+impl Empty::Visitor for Index {
+    type Output = u16;
+    fn visit<T: Empty::Trait>(self) -> u16 {
+        T::INDEX
+    }
+}
+
+/// Without dispatch, a trait can use its own Visitor.
+pub trait Visitor {
+    fn visit(&mut self, name: &str);
+}
+
+#[retrieve]
+trait Node {
+    const NAME: &str = "";
+    fn accept(visitor: &mut dyn Visitor) {
+        visitor.visit(Self::NAME);
+    }
+}
+
+#[iterate]
+fn accept_all<T: Node>(visitor: &mut dyn Visitor) {
+    T::accept(visitor);
+}
+
+struct Names(Vec<&'static str>);
+impl Visitor for Names {
+    fn visit(&mut self, name: &str) {
+        self.0.push(if name == "leaf" { "leaf" } else { "other" });
+    }
+}
+
+#[test]
+fn main() {
+    let mut output = vec!["Old".to_owned()];
+    assert_eq!(Command::dispatch(0, Run(&mut output)), Some("clear"));
+    assert_eq!(Command::dispatch(1, Run(&mut output)), Some("greet"));
+    assert_eq!(Command::dispatch(2, Run(&mut output)), Some("nothing"));
+    assert_eq!(Command::dispatch(3, Run(&mut output)), None);
+    assert_eq!(Command::dispatch(u16::MAX, Run(&mut output)), None);
+    assert_eq!(output, ["Hello!"]);
+
+    let indices: Vec<Option<u16>> = (0..4)
+        .map(|index| Command::dispatch(index, Index))
+        .collect();
+    assert_eq!(indices, [Some(1), Some(2), Some(3), None]);
+
+    assert_eq!(Empty::dispatch(0, Index), None);
+
+    let mut names = Names(vec![]);
+    accept_all(&mut names);
+    assert_eq!(names.0, ["leaf"]);
+}
+
+#[send]
+impl Command {
+    const NAME: &str = "clear";
+    fn run(input: &mut Vec<String>) {
+        input.clear();
+    }
+}
+
+#[send]
+impl Command {
+    const NAME: &str = "greet";
+    fn run(input: &mut Vec<String>) {
+        input.push("Hello!".to_owned());
+    }
+}
+
+#[send]
+impl Command {
+    const NAME: &str = "nothing";
+}
+
+#[send]
+impl Node {
+    const NAME: &str = "leaf";
+}
@@ -0,0 +1,61 @@
+//! Tests explicit mode, where every implementation is sent with its index.
+
+use retrieval::*;
+
+#[retrieve(capacity = 10, explicit, collect_consts)]
+trait Message {
+    const STR: &str = "";
+}
+
+#[retrieve(explicit, 10)]
+trait Empty {}
+
+#[retrieve(explicit, 10)]
+struct Route {
+    path: &'static str,
+}
+
+#[iterate(10, order = forward)]
+fn messages<T: Message>(messages: &mut Vec<&'static str>) {
+    messages.push(T::STR);
+}
+
+const _: () = assert!(Message::QUANTITY == 3);
+const _: () = assert!(Empty::QUANTITY == 0);
+
+#[test]
+fn main() {
+    assert_eq!(Message::ALL_STR, ["first", "second", "third"]);
+
+    let mut collected = vec![];
+    messages(&mut collected);
+    assert_eq!(collected, ["first", "second", "third"]);
+
+    let paths: Vec<&str> = Route::ALL.iter().map(|route| route.path).collect();
+    assert_eq!(paths, ["/", "/about"]);
+}
+
+#[send(index = 3)]
+impl Message {
+    const STR: &str = "third";
+}
+
+#[send(index = 1)]
+impl Message {
+    const STR: &str = "first";
+}
+
+mod elsewhere {
+    use super::*;
+
+    #[send(index = 2)]
+    impl Message {
+        const STR: &str = "second";
+    }
+
+    #[send(index = 2)]
+    const _: Route = Route { path: "/about" };
+}
+
+#[send(index = 1)]
+const _: Route = Route { path: "/" };
@@ -0,0 +1,50 @@
+//! Tests that iterate functions can return a value by folding over every implementation.
+
+use retrieval::*;
+
+#[retrieve]
+trait Cost {
+    const COST: u32 = 0;
+    const NAME: &str = "";
+}
+
+#[iterate]
+const fn total<T: Cost>(acc: u32) -> u32 {
+    acc + T::COST
+}
+
+#[iterate]
+const fn longest<T: Cost>(mut longest: usize, minimum: usize) -> usize {
+    if T::NAME.len() > longest && T::NAME.len() >= minimum {
+        longest = T::NAME.len();
+    }
+    longest
+}
+
+const _: () = assert!(total(0) == 6);
+const _: () = assert!(longest(0, 0) == 6);
+
+#[test]
+fn main() {
+    assert_eq!(total(10), 16);
+    assert_eq!(longest(0, 7), 0);
+    assert_eq!(longest(0, 5), 6);
+}
+
+#[send]
+impl Cost {
+    const COST: u32 = 1;
+    const NAME: &str = "one";
+}
+
+#[send]
+impl Cost {
+    const COST: u32 = 2;
+    const NAME: &str = "two";
+}
+
+#[send]
+impl Cost {
+    const COST: u32 = 3;
+    const NAME: &str = "threee";
+}
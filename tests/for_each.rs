@@ -0,0 +1,59 @@
+//! Tests running a closure for every implementation, in expression position.
+
+use retrieval::*;
+
+#[retrieve(10)]
+trait Message {
+    const STR: &str = "";
+}
+
+#[retrieve(10)]
+trait Empty {}
+
+const TOTAL_LENGTH: usize = {
+    let mut length = 0;
+    for_each!(Message, 10, |T| length += T::STR.len());
+    length
+};
+const _: () = assert!(TOTAL_LENGTH == 11);
+
+fn nothing() -> u8 {
+    let mut count = 0;
+    for_each!(Empty, capacity = 10, |_T| count += 1);
+    count
+}
+
+#[test]
+fn main() {
+    let separator = ", ";
+    let mut messages = String::new();
+    for_each!(Message, 10, |T| {
+        if !messages.is_empty() {
+            messages.push_str(separator);
+        }
+        messages.push_str(T::STR);
+    });
+    assert_eq!(messages, "three, two, one");
+
+    let mut forward = vec![];
+    for_each!(Message, order = forward, capacity = 10, move |T| forward
+        .push((T::INDEX, T::STR)));
+    assert_eq!(forward, [(1, "one"), (2, "two"), (3, "three")]);
+
+    assert_eq!(nothing(), 0);
+}
+
+#[send]
+impl Message {
+    const STR: &str = "one";
+}
+
+#[send]
+impl Message {
+    const STR: &str = "two";
+}
+
+#[send]
+impl Message {
+    const STR: &str = "three";
+}
@@ -0,0 +1,56 @@
+//! Tests sending free functions directly.
+
+use retrieval::*;
+
+#[retrieve(capacity = 10, collect_fns, collect_consts, enum)]
+trait Handler {
+    const NAME: &str = "";
+    const PRIORITY: u8 = 0;
+    fn call(_input: &mut Vec<String>) {}
+}
+
+#[retrieve(capacity = 10, collect_fns)]
+trait Parser {
+    fn parse(_input: &str) -> Option<u32> {
+        None
+    }
+}
+
+#[send(Handler)]
+fn on_start(input: &mut Vec<String>) {
+    input.push("start".to_owned());
+}
+
+#[send(Handler, const NAME: &str = "stop", const PRIORITY: u8 = 2, name = Stop)]
+fn on_stop(input: &mut Vec<String>) {
+    input.push("stop".to_owned());
+}
+
+#[send(Parser, fn = parse)]
+const fn parse_zero(_input: &str) -> Option<u32> {
+    Some(0)
+}
+
+#[send(fn = parse, Parser)]
+fn parse_number(input: &str) -> Option<u32> {
+    input.parse().ok()
+}
+
+const ZERO: Option<u32> = parse_zero("");
+
+#[test]
+fn main() {
+    let mut output = vec![];
+    on_start(&mut output);
+    for call in Handler::CALL {
+        call(&mut output);
+    }
+    assert_eq!(output, ["start", "start", "stop"]);
+
+    assert_eq!(Handler::ALL_NAME, ["", "stop"]);
+    assert_eq!(*Handler::Kind::Stop.priority(), 2);
+
+    assert_eq!(ZERO, Some(0));
+    let parsed: Vec<Option<u32>> = Parser::PARSE.iter().map(|parse| parse("12")).collect();
+    assert_eq!(parsed, [Some(0), Some(12)]);
+}
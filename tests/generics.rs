@@ -0,0 +1,66 @@
+//! Tests that iterate functions can have other generics alongside the retrieval generic.
+
+use core::fmt::Write;
+use retrieval::*;
+
+#[retrieve]
+trait Message {
+    const STR: &str = "";
+}
+
+#[iterate]
+fn dump<T: Message, W>(out: &mut W)
+where
+    W: Write,
+{
+    let _ = out.write_str(T::STR);
+}
+
+#[iterate]
+const fn fill<T: Message, const N: usize>(lengths: &mut [usize; N], index: &mut usize) {
+    lengths[*index] = T::STR.len();
+    *index += 1;
+}
+
+#[iterate]
+fn longest<'a, T: Message>(longest: &'a str, default: &'a str) -> &'a str {
+    if T::STR.len() > longest.len() {
+        T::STR
+    } else if longest.is_empty() {
+        default
+    } else {
+        longest
+    }
+}
+
+const LENGTHS: [usize; 4] = {
+    let mut lengths = [0; 4];
+    let mut index = 0;
+    fill(&mut lengths, &mut index);
+    lengths
+};
+
+#[test]
+fn main() {
+    let mut out = String::new();
+    dump(&mut out);
+    assert_eq!(out, "ccbbbba");
+
+    assert_eq!(LENGTHS, [2, 4, 1, 0]);
+    assert_eq!(longest("", "nothing"), "bbbb");
+}
+
+#[send]
+impl Message {
+    const STR: &str = "a";
+}
+
+#[send]
+impl Message {
+    const STR: &str = "bbbb";
+}
+
+#[send]
+impl Message {
+    const STR: &str = "cc";
+}
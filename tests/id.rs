@@ -0,0 +1,49 @@
+//! Tests that retrieval traits with the same name are counted separately when given different ids.
+
+use retrieval::*;
+
+#[retrieve(collect_consts, 10)]
+trait Message {
+    const STR: &str = "";
+}
+
+mod chat {
+    use retrieval::*;
+
+    #[retrieve(id = "chat", collect_consts, collect_fns, 10)]
+    pub trait Message {
+        const STR: &str = "";
+        fn call() {}
+    }
+
+    #[send(id = "chat")]
+    impl Message {
+        const STR: &str = "chat";
+    }
+}
+
+use chat::Message as Chat;
+
+#[send]
+impl Message {
+    const STR: &str = "first";
+}
+
+// A renamed trait is sent using its id.
+#[send(id = "chat", also = [Message])]
+impl Chat {
+    const STR: &str = "renamed";
+}
+
+#[send(Chat, id = "chat", const STR: &str = "function")]
+fn function() {}
+
+const _: () = assert!(Message::QUANTITY == 2);
+const _: () = assert!(chat::Message::QUANTITY == 3);
+
+#[test]
+fn main() {
+    assert_eq!(Message::ALL_STR, ["first", "renamed"]);
+    assert_eq!(chat::Message::ALL_STR, ["chat", "renamed", "function"]);
+    chat::Message::CALL[2]();
+}
@@ -0,0 +1,89 @@
+//! Tests that retrieval traits can name each implementation with a Kind.
+
+use retrieval::*;
+
+#[retrieve(capacity = 10, enum, dispatch)]
+trait Command {
+    const NAME: &str = "";
+    const ALIASES: &[&str] = &[];
+    const OWNED: Option<Vec<u8>> = None;
+    fn run(_input: &mut Vec<String>) {}
+    fn describe(input: &str) -> &str {
+        input
+    }
+    unsafe fn dangerous() -> u8 {
+        0
+    }
+}
+
+#[retrieve(enum, 10)]
+trait Empty {}
+
+struct Struct {
+    command: Command::Kind,
+}
+
+const QUIT: Command::Kind = Command::Kind::Quit;
+const _: () = assert!(Command::Kind::COUNT == 3);
+const _: () = assert!(QUIT.index() == 2);
+const _: () = assert!(Command::Kind::from_index(3).is_none());
+const _: () = assert!(Empty::Kind::ALL.is_empty());
+
+fn name(command: Command::Kind) -> &'static str {
+    match command {
+        Command::Kind::Clear => "clear",
+        Command::Kind::Quit => "quit",
+        _ => "unnamed",
+    }
+}
+
+struct Index;
+
+impl Command::Visitor for Index {
+    type Output = u16;
+    fn visit<T: Command::Trait>(self) -> u16 {
+        T::INDEX
+    }
+}
+
+#[test]
+fn main() {
+    let names: Vec<&str> = Command::Kind::ALL.into_iter().map(name).collect();
+    assert_eq!(names, ["clear", "unnamed", "quit"]);
+
+    let stored = Struct {
+        command: Command::Kind::from_index(1).expect("There are 3 commands."),
+    };
+    let copy = stored.command;
+    assert_eq!(copy, stored.command);
+    assert_eq!(*copy.name(), "echo");
+    assert_eq!(*Command::Kind::Quit.aliases(), ["exit", "q"]);
+    assert_eq!(*Command::Kind::Clear.owned(), None);
+
+    let mut output = vec!["Old".to_owned()];
+    Command::Kind::Clear.run(&mut output);
+    assert!(output.is_empty());
+    assert_eq!(Command::Kind::Quit.describe("Bye"), "Bye");
+    assert_eq!(unsafe { Command::Kind::Quit.dangerous() }, 0);
+
+    assert_eq!(Command::Kind::Quit.dispatch(Index), 3);
+}
+
+#[send(name = Clear)]
+impl Command {
+    const NAME: &str = "clear";
+    fn run(input: &mut Vec<String>) {
+        input.clear();
+    }
+}
+
+#[send]
+impl Command {
+    const NAME: &str = "echo";
+}
+
+#[send(name = Quit)]
+impl Command {
+    const NAME: &str = "quit";
+    const ALIASES: &[&str] = &["exit", "q"];
+}
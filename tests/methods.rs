@@ -0,0 +1,91 @@
+//! Tests that iterate works on associated functions and methods inside impl blocks.
+
+use retrieval::*;
+
+#[derive(Default)]
+struct Engine {
+    log: Vec<&'static str>,
+}
+
+#[retrieve]
+trait System {
+    const NAME: &str = "";
+    fn run(_engine: &mut Engine) {}
+}
+
+impl Engine {
+    #[iterate]
+    fn run_systems<S: System>(&mut self) {
+        S::run(self);
+    }
+
+    // Extra bounds mean each system is called individually.
+    #[iterate(4)]
+    fn run_systems_again<S: System + Sized>(&mut self) {
+        S::run(self);
+    }
+
+    #[iterate]
+    fn count_logged<S: System>(&self, acc: usize) -> usize {
+        acc + self.log.iter().filter(|name| **name == S::NAME).count()
+    }
+
+    #[iterate]
+    const fn count<S: System>(acc: usize) -> usize {
+        acc + 1
+    }
+
+    #[iterate]
+    fn with_systems<S: System>(mut engine: Self) -> Self {
+        S::run(&mut engine);
+        engine
+    }
+}
+
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+impl Describe for Engine {
+    fn describe(&self) -> String {
+        #[iterate]
+        fn describe_all<S: System>(out: &mut String) {
+            out.push_str(S::NAME);
+        }
+
+        let mut out = String::new();
+        describe_all(&mut out);
+        out
+    }
+}
+
+const _: () = assert!(Engine::count(0) == 2);
+
+#[test]
+fn main() {
+    let mut engine = Engine::default();
+    engine.run_systems();
+    assert_eq!(engine.log, ["physics", "input"]);
+    assert_eq!(engine.count_logged(0), 2);
+    assert_eq!(Engine::with_systems(Engine::default()).log, engine.log);
+    engine.run_systems_again();
+    assert_eq!(engine.count_logged(0), 4);
+
+    assert_eq!(engine.describe(), "physicsinput");
+}
+
+#[send]
+impl System {
+    const NAME: &str = "input";
+    fn run(engine: &mut Engine) {
+        engine.log.push(Self::NAME);
+    }
+}
+
+#[send]
+impl System {
+    const NAME: &str = "physics";
+    fn run(engine: &mut Engine) {
+        engine.log.push(Self::NAME);
+    }
+}
@@ -0,0 +1,113 @@
+//! Tests sending named types, which are linked into the chain through ITEM.
+
+use Message::Trait as _;
+use Plugin::Trait as _;
+use retrieval::*;
+
+#[retrieve(capacity = 10, collect_consts, collect_fns)]
+trait Plugin {
+    const NAME: &str = "";
+    type Config = ();
+    fn describe() -> String {
+        String::new()
+    }
+    fn greet(&self) -> String {
+        String::new()
+    }
+    fn generic<T: Default>() -> T {
+        T::default()
+    }
+    fn make<T: From<u8>>(_seed: impl Into<u8>) -> T {
+        T::from(0)
+    }
+}
+
+#[iterate(10)]
+fn generic_sum<T: Plugin>(acc: u8) -> u8 {
+    acc + T::generic::<u8>() + 1
+}
+
+#[retrieve(10)]
+trait Message {
+    const STR: &str = "";
+}
+
+#[derive(Default)]
+pub struct Window {
+    title: String,
+}
+
+#[send]
+impl Plugin for Window {
+    const NAME: &str = "window";
+    type Config = u8;
+    fn describe() -> String {
+        "A window.".to_owned()
+    }
+    fn greet(&self) -> String {
+        format!("Window {}", self.title)
+    }
+    fn make<T: From<u8>>(seed: impl Into<u8>) -> T {
+        T::from(seed.into())
+    }
+}
+
+#[derive(Default)]
+pub struct Audio;
+
+#[send]
+impl Plugin for Audio {
+    const NAME: &str = "audio";
+    type Config = ();
+    fn greet(&self) -> String {
+        "Audio".to_owned()
+    }
+    fn generic<T: Default>() -> T {
+        T::default()
+    }
+}
+
+#[iterate(10)]
+fn greet_all<T: Plugin>(greetings: &mut Vec<String>)
+where
+    T::ITEM: Default + Sized,
+{
+    greetings.push(T::ITEM::default().greet());
+}
+
+// Generic arguments can be given alongside impl trait, so make is forwarded to Window's.
+#[iterate(10)]
+fn make_all<T: Plugin>(made: &mut Vec<u32>) {
+    made.push(T::make::<u32>(7_u8));
+}
+
+#[iterate(10)]
+fn item_matches<T: Message>(acc: bool) -> bool {
+    acc && T::ITEM::STR == T::STR && T::ITEM::INDEX == T::INDEX
+}
+
+#[test]
+fn main() {
+    assert_eq!(<Window as Plugin::Trait>::NAME, "window");
+    assert_eq!(<Audio as Plugin::Trait>::INDEX, 2);
+
+    assert_eq!(Plugin::ALL_NAME, ["window", "audio"]);
+    let descriptions: Vec<String> = Plugin::DESCRIBE.iter().map(|describe| describe()).collect();
+    assert_eq!(descriptions, ["A window.", ""]);
+
+    let mut greetings = vec![];
+    greet_all(&mut greetings);
+    assert_eq!(greetings, ["Audio", "Window "]);
+
+    let mut made = vec![];
+    make_all(&mut made);
+    assert_eq!(made, [0, 7]);
+
+    assert!(item_matches(true));
+    assert_eq!(generic_sum(0), 2);
+}
+
+#[send]
+impl Message {
+    const STR: &str = "anonymous";
+}
@@ -0,0 +1,78 @@
+//! Tests that iterate functions can visit implementations in the order they were sent.
+
+use core::ops::ControlFlow;
+use retrieval::*;
+
+#[retrieve(10)]
+trait Plugin {
+    const NAME: &str = "";
+    const PRIORITY: u8 = 0;
+}
+
+#[iterate(order = forward)]
+fn initialise<T: Plugin>(names: &mut Vec<&'static str>) {
+    names.push(T::NAME);
+}
+
+#[iterate(order = reverse)]
+fn shut_down<T: Plugin>(names: &mut Vec<&'static str>) {
+    names.push(T::NAME);
+}
+
+#[iterate(capacity = 10, order = forward)]
+const fn priorities<T: Plugin>(acc: u32) -> u32 {
+    acc * 10 + T::PRIORITY as u32
+}
+
+#[iterate(order = forward, 10)]
+const fn first_important<T: Plugin>(minimum: u8) -> ControlFlow<&'static str> {
+    if T::PRIORITY >= minimum {
+        ControlFlow::Break(T::NAME)
+    } else {
+        ControlFlow::Continue(())
+    }
+}
+
+#[iterate(10, order = forward)]
+fn checked_names<T: Plugin + Sync>(names: &mut Vec<&'static str>) {
+    names.push(T::NAME);
+}
+
+const _: () = assert!(priorities(0) == 132);
+const _: () = assert!(first_important(4).is_none());
+
+#[test]
+fn main() {
+    let mut names = vec![];
+    initialise(&mut names);
+    assert_eq!(names, ["window", "audio", "input"]);
+
+    names.clear();
+    shut_down(&mut names);
+    assert_eq!(names, ["input", "audio", "window"]);
+
+    assert_eq!(first_important(1), Some("window"));
+    assert_eq!(first_important(2), Some("audio"));
+
+    names.clear();
+    checked_names(&mut names);
+    assert_eq!(names, ["window", "audio", "input"]);
+}
+
+#[send]
+impl Plugin {
+    const NAME: &str = "window";
+    const PRIORITY: u8 = 1;
+}
+
+#[send]
+impl Plugin {
+    const NAME: &str = "audio";
+    const PRIORITY: u8 = 3;
+}
+
+#[send]
+impl Plugin {
+    const NAME: &str = "input";
+    const PRIORITY: u8 = 2;
+}
@@ -0,0 +1,116 @@
+//! Tests that iterate functions accept any pattern in their inputs.
+
+use retrieval::*;
+
+#[retrieve]
+trait Number {
+    const N: u8 = 0;
+}
+
+#[derive(Clone, Copy)]
+struct Wrapper(u8);
+
+#[iterate]
+const fn sum_pairs<T: Number>((a, b): (u8, u8), Wrapper(c): Wrapper, total: &mut u32) {
+    *total += (a + b + c + T::N) as u32;
+}
+
+#[iterate]
+const fn largest<T: Number>(mut candidate: u8, largest: &mut u8) {
+    if T::N > candidate {
+        candidate = T::N;
+    }
+    if candidate > *largest {
+        *largest = candidate;
+    }
+}
+
+#[iterate]
+const fn count_and_sum<T: Number>((count, sum): (u8, u8)) -> (u8, u8) {
+    (count + 1, sum + T::N)
+}
+
+#[iterate(4)]
+fn doubled<T: Number + Sized>(mut acc: Vec<u8>, [factor]: [u8; 1]) -> Vec<u8> {
+    acc.push(T::N * factor);
+    acc
+}
+
+#[iterate(4)]
+fn print_with<T: Number>(prefix: String, out: &mut Vec<String>) {
+    out.push(format!("{prefix}{}", T::N));
+}
+
+// Changes to a mutable input are passed on to the next implementation.
+#[iterate(4)]
+fn shout_with<T: Number>(mut prefix: String, out: &mut Vec<String>) {
+    prefix.push('!');
+    out.push(format!("{prefix}{}", T::N));
+}
+
+struct Named {
+    name: String,
+}
+
+// Destructured inputs are rebuilt to be passed on, so they don't need to be Copy.
+#[iterate(4)]
+fn join<T: Number>((a, b): (String, String), Named { name }: Named, out: &mut Vec<String>) {
+    out.push(format!("{a}{b}{name}{}", T::N));
+}
+
+#[iterate]
+fn write_all<T: Number>(out: &mut impl core::fmt::Write) {
+    let _ = write!(out, "{}", T::N);
+}
+
+const TOTAL: u32 = {
+    let mut total = 0;
+    sum_pairs((1, 2), Wrapper(3), &mut total);
+    total
+};
+const _: () = assert!(TOTAL == 6 + 6 + 15);
+
+const LARGEST: u8 = {
+    let mut largest = 0;
+    self::largest(0, &mut largest);
+    largest
+};
+const _: () = assert!(LARGEST == 10);
+
+#[test]
+fn main() {
+    assert_eq!(count_and_sum((0, 0)), (2, 15));
+    assert_eq!(doubled(vec![], [2]), [20, 10]);
+
+    let mut out = String::new();
+    write_all(&mut out);
+    assert_eq!(out, "105");
+
+    let mut printed = vec![];
+    print_with(String::from("N = "), &mut printed);
+    assert_eq!(printed, ["N = 10", "N = 5"]);
+
+    let mut shouted = vec![];
+    shout_with(String::from("N"), &mut shouted);
+    assert_eq!(shouted, ["N!10", "N!!5"]);
+
+    let mut joined = vec![];
+    join(
+        (String::from("a"), String::from("b")),
+        Named {
+            name: String::from("c"),
+        },
+        &mut joined,
+    );
+    assert_eq!(joined, ["abc10", "abc5"]);
+}
+
+#[send]
+impl Number {
+    const N: u8 = 5;
+}
+
+#[send]
+impl Number {
+    const N: u8 = 10;
+}
@@ -0,0 +1,71 @@
+//! Tests that implementations know their INDEX, and that iterate functions can receive their position.
+
+use retrieval::*;
+
+#[retrieve(10)]
+trait Message {
+    const STR: &str = "";
+    const ORDINAL: u16 = 0;
+}
+
+#[iterate]
+const fn collect_messages<T: Message>(messages: &mut [&str], #[position] position: usize) {
+    messages[position] = T::STR;
+}
+
+#[iterate(order = forward)]
+const fn collect_ordinals<T: Message>(#[position] position: u8, ordinals: &mut [(u8, u16)]) {
+    ordinals[position as usize] = (position, T::INDEX);
+}
+
+#[iterate(10)]
+fn positions_checked<T: Message + Sync>(
+    positions: &mut Vec<(u16, u16)>,
+    #[position] mut position: u16,
+) {
+    position += 100;
+    positions.push((position, T::ORDINAL));
+}
+
+#[iterate]
+const fn last_position<T: Message>(acc: usize, #[position] position: usize) -> usize {
+    if acc > position { acc } else { position }
+}
+
+const MESSAGES: [&str; Message::QUANTITY as usize] = {
+    let mut messages = [""; Message::QUANTITY as usize];
+    collect_messages(&mut messages);
+    messages
+};
+const _: () = assert!(last_position(0) == 2);
+
+#[test]
+fn main() {
+    assert_eq!(MESSAGES, ["third", "second", "first"]);
+
+    let mut ordinals = [(0, 0); Message::QUANTITY as usize];
+    collect_ordinals(&mut ordinals);
+    assert_eq!(ordinals, [(0, 1), (1, 2), (2, 3)]);
+
+    let mut positions = vec![];
+    positions_checked(&mut positions);
+    assert_eq!(positions, [(100, 3), (101, 2), (102, 1)]);
+}
+
+#[send]
+impl Message {
+    const STR: &str = "first";
+    const ORDINAL: u16 = Self::INDEX;
+}
+
+#[send]
+impl Message {
+    const STR: &str = "second";
+    const ORDINAL: u16 = Self::INDEX;
+}
+
+#[send]
+impl Message {
+    const STR: &str = "third";
+    const ORDINAL: u16 = Self::INDEX;
+}
@@ -0,0 +1,34 @@
+//! Iterate and for_each reach retrieval traits without being given a capacity, even through a renamed dependency, and from another crate.
+
+use renamed::*;
+use retrieval_build_example::Greeting;
+
+#[retrieve(3)]
+trait Number {
+    const NUMBER: u8 = 0;
+}
+
+#[send]
+impl Number {
+    const NUMBER: u8 = 5;
+}
+
+const SUM: u8 = {
+    let mut sum = 0;
+    for_each!(Number, |T| sum += T::NUMBER);
+    sum
+};
+const _: () = assert!(SUM == 5);
+
+const LENGTH: usize = {
+    let mut length = 0;
+    for_each!(Greeting, order = forward, |T| length += T::STR.len());
+    length
+};
+const _: () = assert!(LENGTH == 37);
+
+#[iterate]
+const fn count<T: Greeting>(count: usize) -> usize {
+    count + 1
+}
+const _: () = assert!(count(0) == Greeting::QUANTITY as usize);
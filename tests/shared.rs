@@ -0,0 +1,70 @@
+//! Tests that retrieval traits can share one pool of switches, while keeping their own indices.
+
+use retrieval::*;
+
+shared_switches!(10);
+
+#[retrieve]
+trait Message {
+    const STR: &str = "";
+}
+
+#[retrieve(id = "costs")]
+trait Cost {
+    const COST: u32 = 0;
+}
+
+#[retrieve]
+trait Empty {}
+
+#[iterate(order = forward)]
+const fn collect_messages<T: Message>(messages: &mut [&str], #[position] position: usize) {
+    messages[position] = T::STR;
+}
+
+const MESSAGES: [&str; Message::QUANTITY as usize] = {
+    let mut messages = [""; Message::QUANTITY as usize];
+    collect_messages(&mut messages);
+    messages
+};
+
+#[iterate]
+const fn total<T: Cost>(acc: u32) -> u32 {
+    acc + T::COST
+}
+
+const _: () = assert!(Message::__internal::SHARED);
+const _: () = assert!(Message::QUANTITY == 3);
+const _: () = assert!(Cost::QUANTITY == 2);
+const _: () = assert!(Empty::QUANTITY == 0);
+const _: () = assert!(total(0) == 12);
+
+#[send]
+impl Message {
+    const STR: &str = "first";
+}
+
+#[send(id = "costs")]
+impl Cost {
+    const COST: u32 = 5;
+}
+
+#[send]
+impl Message {
+    const STR: &str = "second";
+}
+
+#[send(id = "costs")]
+impl Cost {
+    const COST: u32 = 7;
+}
+
+#[send]
+impl Message {
+    const STR: &str = "third";
+}
+
+#[test]
+fn main() {
+    assert_eq!(MESSAGES, ["first", "second", "third"]);
+}
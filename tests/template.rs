@@ -0,0 +1,64 @@
+//! Tests sending a copy of an item for every type in a list.
+
+use retrieval::*;
+
+#[retrieve(capacity = 10, collect_consts)]
+trait Codec {
+    type Ty = ();
+    const NAME: &str = "";
+    const SIZE: usize = 0;
+    fn encode(_value: u64) -> Vec<u8> {
+        vec![]
+    }
+}
+
+#[retrieve(capacity = 10)]
+struct Width {
+    bits: u32,
+}
+
+#[send(for T in [u8, u16, u32, u64])]
+impl Codec {
+    type Ty = T;
+    const NAME: &str = stringify!(T);
+    const SIZE: usize = size_of::<T>();
+    fn encode(value: u64) -> Vec<u8> {
+        value.to_le_bytes()[..size_of::<T>()].to_vec()
+    }
+}
+
+#[send(for T in [Vec<u8>], also = [])]
+impl Codec {
+    type Ty = T;
+    const NAME: &str = stringify!(T);
+}
+
+#[send(for T in [u8, u16])]
+const _: Width = Width { bits: T::BITS };
+
+#[iterate(10)]
+fn encode_all<T: Codec>(value: u64, encoded: &mut Vec<Vec<u8>>) {
+    encoded.push(T::encode(value));
+}
+
+#[test]
+fn main() {
+    assert_eq!(Codec::ALL_NAME, ["u8", "u16", "u32", "u64", "Vec < u8 >"]);
+    assert_eq!(Codec::ALL_SIZE, [1, 2, 4, 8, 0]);
+
+    let mut encoded = vec![];
+    encode_all(258, &mut encoded);
+    assert_eq!(
+        encoded,
+        [
+            vec![],
+            vec![2, 1, 0, 0, 0, 0, 0, 0],
+            vec![2, 1, 0, 0],
+            vec![2, 1],
+            vec![2],
+        ]
+    );
+
+    let bits: Vec<u32> = Width::ALL.iter().map(|width| width.bits).collect();
+    assert_eq!(bits, [8, 16]);
+}
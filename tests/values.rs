@@ -0,0 +1,51 @@
+//! Tests retrieving plain values instead of implementations.
+
+use retrieval::*;
+
+#[retrieve(10)]
+struct Route {
+    path: &'static str,
+    handler: fn() -> u8,
+}
+
+#[retrieve(capacity = 10)]
+#[derive(Debug, PartialEq)]
+enum Setting {
+    Flag(bool),
+    Level(u8),
+}
+
+#[retrieve(10)]
+struct Empty;
+
+const _: () = assert!(Route::QUANTITY == 2);
+const _: () = assert!(Empty::ALL.is_empty());
+
+#[test]
+fn main() {
+    let paths: Vec<&str> = Route::ALL.iter().map(|route| route.path).collect();
+    assert_eq!(paths, ["/", "/about"]);
+    let handled: Vec<u8> = Route::ALL.iter().map(|route| (route.handler)()).collect();
+    assert_eq!(handled, [0, 1]);
+
+    assert_eq!(ABOUT.path, "/about");
+    assert_eq!(Setting::ALL, [Setting::Flag(true), Setting::Level(3)]);
+}
+
+#[send]
+const _: Route = Route {
+    path: "/",
+    handler: || 0,
+};
+
+#[send]
+const ABOUT: Route = Route {
+    path: "/about",
+    handler: || 1,
+};
+
+#[send]
+const _: Setting = Setting::Flag(true);
+
+#[send]
+const _: Setting = Setting::Level(3);